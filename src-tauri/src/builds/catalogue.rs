@@ -0,0 +1,168 @@
+use futures_util::stream::{ self, StreamExt };
+use serde::{ Deserialize, Serialize };
+use std::cmp::Ordering;
+use tauri::command;
+
+use super::download_manager::{ VersionListing, cached_manifest, fetch_manifest, fetch_versions };
+use super::response_cache::Cached;
+use super::version::{ BuildVersion, ReleaseNumber };
+use crate::error::LauncherError;
+
+/// Manifests fetched at once while building the catalogue.
+const MANIFEST_CONCURRENCY: usize = 8;
+
+#[derive(Clone, Serialize)]
+pub struct CatalogueEntry {
+    version: String,
+    /// The `major.minor` release, e.g. `12.41`.
+    release: Option<String>,
+    changelist: Option<u64>,
+    chapter: Option<u32>,
+    /// Season within the chapter.
+    season: Option<u32>,
+    total_size: Option<u64>,
+    file_count: Option<usize>,
+    manifest_available: bool,
+    release_date: Option<String>,
+    #[serde(skip)]
+    parsed: Option<BuildVersion>,
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogueSort {
+    #[default]
+    Version,
+    Changelist,
+    Size,
+    ReleaseDate,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct CatalogueQuery {
+    chapter: Option<u32>,
+    season: Option<u32>,
+    /// Leave out versions no manifest mirror has.
+    available_only: bool,
+    sort: CatalogueSort,
+    descending: bool,
+}
+
+impl CatalogueEntry {
+    fn from_listing(listing: &VersionListing) -> Self {
+        let parsed = BuildVersion::parse(listing.version()).ok();
+        let number = parsed.as_ref().and_then(|parsed| parsed.number);
+        let (chapter, season) = match number.and_then(season_of) {
+            Some((chapter, season)) => (Some(chapter), Some(season)),
+            None => (None, None),
+        };
+
+        Self {
+            version: listing.version().to_string(),
+            release: number.map(|number| number.to_string()),
+            changelist: parsed.as_ref().and_then(|parsed| parsed.changelist),
+            chapter,
+            season,
+            total_size: None,
+            file_count: None,
+            manifest_available: false,
+            release_date: listing.release_date().map(str::to_string),
+            parsed,
+        }
+    }
+
+    fn matches(&self, query: &CatalogueQuery) -> bool {
+        query.chapter.is_none_or(|chapter| self.chapter == Some(chapter)) &&
+            query.season.is_none_or(|season| self.season == Some(season))
+    }
+}
+
+/// Chapter and season for a release. Chapter 1 ran seasons 1 to 10 on majors 1 to 10,
+/// except that season 2 started partway through 1.x; later chapters begin at fixed
+/// majors.
+fn season_of(number: ReleaseNumber) -> Option<(u32, u32)> {
+    let ReleaseNumber { major, minor, .. } = number;
+
+    match major {
+        1 if minor < 8 => Some((1, 1)),
+        1 | 2 => Some((1, 2)),
+        3..=10 => Some((1, major)),
+        11..=18 => Some((2, major - 10)),
+        19..=22 => Some((3, major - 18)),
+        23..=27 => Some((4, major - 22)),
+        28..=32 => Some((5, major - 27)),
+        33..=36 => Some((6, major - 32)),
+        _ => None,
+    }
+}
+
+/// Oldest first by default; entries missing the sort key go last either way.
+fn compare(a: &CatalogueEntry, b: &CatalogueEntry, query: &CatalogueQuery) -> Ordering {
+    let ordering = match query.sort {
+        CatalogueSort::Version => {
+            compare_present(a.parsed.as_ref(), b.parsed.as_ref(), query.descending)
+        }
+        CatalogueSort::Changelist => compare_present(a.changelist, b.changelist, query.descending),
+        CatalogueSort::Size => compare_present(a.total_size, b.total_size, query.descending),
+        CatalogueSort::ReleaseDate => {
+            compare_present(a.release_date.as_ref(), b.release_date.as_ref(), query.descending)
+        }
+    };
+
+    ordering.then_with(|| a.version.cmp(&b.version))
+}
+
+fn compare_present<T: Ord>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if descending => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Every version on the version mirrors with what its manifest says about it, filtered
+/// and sorted by `query`. Manifests come through the same cache as
+/// `get_manifest_for_version`, so only new versions cost a download. When the version
+/// list itself is stale the launcher is assumed offline and only cached manifests are
+/// used.
+#[command]
+pub async fn get_version_catalogue(
+    query: Option<CatalogueQuery>
+) -> Result<Cached<Vec<CatalogueEntry>>, LauncherError> {
+    let query = query.unwrap_or_default();
+    let versions = fetch_versions().await?;
+    let offline = versions.stale;
+
+    let entries: Vec<CatalogueEntry> = versions.data
+        .iter()
+        .map(CatalogueEntry::from_listing)
+        .filter(|entry| entry.matches(&query))
+        .collect();
+
+    let mut entries: Vec<CatalogueEntry> = stream
+        ::iter(entries)
+        .map(|mut entry| async move {
+            let manifest = if offline {
+                cached_manifest(&entry.version).await
+            } else {
+                fetch_manifest(&entry.version).await.ok()
+            };
+
+            if let Some(manifest) = manifest {
+                entry.total_size = Some(manifest.data.size.max(0) as u64);
+                entry.file_count = Some(manifest.data.chunks.len());
+                entry.manifest_available = true;
+            }
+            entry
+        })
+        .buffered(MANIFEST_CONCURRENCY)
+        .filter(|entry| std::future::ready(!query.available_only || entry.manifest_available))
+        .collect().await;
+
+    entries.sort_by(|a, b| compare(a, b, &query));
+
+    Ok(versions.map(|_| entries))
+}
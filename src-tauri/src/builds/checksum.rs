@@ -0,0 +1,172 @@
+use md5::Md5;
+use serde::Deserialize;
+use sha1::Sha1;
+use sha2::{ Digest, Sha256 };
+use std::fs::File;
+use std::io::{ self, Read };
+use std::path::Path;
+
+use crate::error::{ ErrorCode, LauncherError };
+
+const HASH_BUFFER_SIZE: usize = 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha256,
+    Sha1,
+    Md5,
+}
+
+impl HashAlgorithm {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().replace('-', "").as_str() {
+            "sha256" => Some(Self::Sha256),
+            "sha1" => Some(Self::Sha1),
+            "md5" => Some(Self::Md5),
+            _ => None,
+        }
+    }
+
+    /// The algorithm a bare hex digest of this length must come from.
+    fn from_hex_len(len: usize) -> Option<Self> {
+        match len {
+            64 => Some(Self::Sha256),
+            40 => Some(Self::Sha1),
+            32 => Some(Self::Md5),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::Sha1 => "sha1",
+            Self::Md5 => "md5",
+        }
+    }
+}
+
+/// A hash a download has to match, written `sha256:<hex>`, `sha1:<hex>` or `md5:<hex>`.
+/// A bare hex digest is read as whichever algorithm has that length.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ExpectedHash {
+    pub algorithm: HashAlgorithm,
+    pub hex: String,
+}
+
+impl ExpectedHash {
+    pub fn parse(text: &str) -> Result<Self, LauncherError> {
+        let invalid = || {
+            LauncherError::invalid_input(
+                format!("Unrecognised checksum: {}", text)
+            ).with_context("checksum", text)
+        };
+
+        let text = text.trim();
+        let (algorithm, hex) = match text.split_once(':') {
+            Some((name, hex)) => (HashAlgorithm::parse(name).ok_or_else(invalid)?, hex.trim()),
+            None => (HashAlgorithm::from_hex_len(text.len()).ok_or_else(invalid)?, text),
+        };
+
+        let valid_hex = hex.chars().all(|c| c.is_ascii_hexdigit());
+        if !valid_hex || HashAlgorithm::from_hex_len(hex.len()) != Some(algorithm) {
+            return Err(invalid());
+        }
+
+        Ok(Self { algorithm, hex: hex.to_ascii_lowercase() })
+    }
+
+    /// Hashes `path` with this hash's algorithm. Blocking.
+    pub fn hash_file(&self, path: &Path) -> io::Result<String> {
+        let reader = File::open(path)?;
+
+        Ok(match self.algorithm {
+            HashAlgorithm::Sha256 => {
+                let mut hasher = Sha256::new();
+                feed(reader, |data| hasher.update(data))?;
+                finalize_hex(hasher)
+            }
+            HashAlgorithm::Sha1 => {
+                let mut hasher = Sha1::new();
+                feed(reader, |data| hasher.update(data))?;
+                format!("{:x}", hasher.finalize())
+            }
+            HashAlgorithm::Md5 => {
+                let mut hasher = Md5::new();
+                feed(reader, |data| hasher.update(data))?;
+                format!("{:x}", hasher.finalize())
+            }
+        })
+    }
+
+    /// Fails with a `ChecksumMismatch` error carrying both hashes when `path` doesn't
+    /// match.
+    pub async fn verify(&self, path: &Path) -> Result<(), LauncherError> {
+        let expected = self.clone();
+        let file = path.to_path_buf();
+        let actual = tokio::task::spawn_blocking(move || expected.hash_file(&file)).await??;
+
+        if hashes_match(&self.hex, &actual) {
+            return Ok(());
+        }
+
+        Err(
+            LauncherError::new(
+                ErrorCode::ChecksumMismatch,
+                format!(
+                    "{} failed {} verification: expected {}, got {}",
+                    path.file_name().unwrap_or(path.as_os_str()).to_string_lossy(),
+                    self.algorithm.name(),
+                    self.hex,
+                    actual
+                )
+            )
+                .with_context("path", path.to_string_lossy().to_string())
+                .with_context("algorithm", self.algorithm.name())
+                .with_context("expected", self.hex.clone())
+                .with_context("actual", actual)
+        )
+    }
+}
+
+impl TryFrom<String> for ExpectedHash {
+    type Error = LauncherError;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        Self::parse(&text)
+    }
+}
+
+pub fn finalize_hex(hasher: Sha256) -> String {
+    format!("{:x}", hasher.finalize())
+}
+
+fn feed(mut reader: impl Read, mut update: impl FnMut(&[u8])) -> io::Result<()> {
+    let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(());
+        }
+        update(&buffer[..read]);
+    }
+}
+
+/// Feeds the first `len` bytes of `path` into a fresh hasher, used to pick up a
+/// running file hash again when a `.part` file is resumed.
+pub fn sha256_prefix(path: &Path, len: u64) -> io::Result<Sha256> {
+    let mut hasher = Sha256::new();
+    feed(File::open(path)?.take(len), |data| hasher.update(data))?;
+    Ok(hasher)
+}
+
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    sha256_prefix(path, u64::MAX).map(finalize_hex)
+}
+
+/// Manifest hashes may be written in either case.
+pub fn hashes_match(expected: &str, actual: &str) -> bool {
+    expected.trim().eq_ignore_ascii_case(actual)
+}
@@ -0,0 +1,349 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{ Path, PathBuf };
+use std::sync::atomic::{ AtomicU64, Ordering };
+use std::time::SystemTime;
+use tauri::command;
+use tokio::fs::File as AsyncFile;
+use tokio::io::{ AsyncWriteExt, BufWriter };
+use tokio::sync::Mutex;
+
+use super::disk_space;
+use super::settings::DownloadSettings;
+use crate::error::{ LauncherError, ResultExt };
+
+/// Once the cache is over its limit, trimming evicts down to this share of it so the
+/// next few chunks don't immediately trigger another pass.
+const TRIM_TARGET_PERCENT: u64 = 90;
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Serialize)]
+pub struct ChunkCacheInfo {
+    path: String,
+    entries: usize,
+    total_bytes: u64,
+    max_bytes: u64,
+}
+
+/// Compressed chunks kept under `~/Solaris/ChunkCache`, shared by every build. Chunks
+/// with a manifest hash are stored by that hash so identical chunks in different
+/// versions are only downloaded once; older manifests fall back to version and id.
+/// The least recently used chunks are evicted once the cache outgrows its limit.
+pub struct ChunkCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    size: AtomicU64,
+    trimming: Mutex<()>,
+}
+
+impl ChunkCache {
+    /// Opens the cache with the configured limit. Returns None when caching is turned
+    /// off by a zero limit.
+    pub async fn open() -> Option<Self> {
+        let max_bytes = DownloadSettings::load().await.chunk_cache_max_bytes;
+        if max_bytes == 0 {
+            return None;
+        }
+
+        let dir = cache_dir()?;
+        let scan_dir = dir.clone();
+        let size = tokio::task
+            ::spawn_blocking(move || {
+                list_entries(&scan_dir)
+                    .iter()
+                    .map(|entry| entry.size)
+                    .sum()
+            }).await
+            .ok()?;
+
+        Some(Self {
+            dir,
+            max_bytes,
+            size: AtomicU64::new(size),
+            trimming: Mutex::new(()),
+        })
+    }
+
+    /// How much the cache may still grow on the volume holding `install_path`, which a
+    /// download has to leave free on top of its own size. Zero when caching is off or
+    /// the cache lives on another volume.
+    pub async fn headroom(install_path: &Path) -> u64 {
+        let max_bytes = DownloadSettings::load().await.chunk_cache_max_bytes;
+        if max_bytes == 0 {
+            return 0;
+        }
+
+        let Some(dir) = cache_dir() else {
+            return 0;
+        };
+        let install_path = install_path.to_path_buf();
+        tokio::task
+            ::spawn_blocking(move || {
+                let cache_mount = disk_space::available_space(&dir).map(|(mount, _)| mount);
+                let install_mount = disk_space
+                    ::available_space(&install_path)
+                    .map(|(mount, _)| mount);
+                if let (Some(cache_mount), Some(install_mount)) = (cache_mount, install_mount) {
+                    if cache_mount != install_mount {
+                        return 0;
+                    }
+                }
+
+                let used: u64 = list_entries(&dir)
+                    .iter()
+                    .map(|entry| entry.size)
+                    .sum();
+                max_bytes.saturating_sub(used)
+            }).await
+            .unwrap_or(max_bytes)
+    }
+
+    pub fn key(release: &str, chunk_id: i32, expected_hash: Option<&str>) -> String {
+        match expected_hash {
+            Some(hash) => hash.to_lowercase(),
+            None => format!("{}-{}", release, chunk_id),
+        }
+    }
+
+    /// Path of the cached chunk for `key`, if there is one, to be streamed from.
+    pub async fn get(&self, key: &str) -> Option<PathBuf> {
+        let path = self.entry_path(key);
+        tokio::fs::metadata(&path).await.ok()?;
+
+        // Bump the modified time so eviction sees this chunk as recently used.
+        let touch_path = path.clone();
+        let _ = tokio::task::spawn_blocking(move || {
+            fs::File::options()
+                .write(true)
+                .open(&touch_path)
+                .and_then(|file| file.set_modified(SystemTime::now()))
+        }).await;
+
+        Some(path)
+    }
+
+    /// Starts writing a chunk into the cache as it downloads. Returns None when the
+    /// chunk is already cached or the cache can't be written to.
+    pub async fn writer(&self, key: &str) -> Option<CacheWriter> {
+        let path = self.entry_path(key);
+        if tokio::fs::metadata(&path).await.is_ok() {
+            return None;
+        }
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.ok()?;
+        }
+
+        // Written through a temp file so a half-written chunk is never served. The name is
+        // unique because a build can contain the same chunk more than once.
+        let temp_path = path.with_extension(
+            format!("{}.tmp", TEMP_COUNTER.fetch_add(1, Ordering::Relaxed))
+        );
+        let file = AsyncFile::create(&temp_path).await.ok()?;
+
+        Some(CacheWriter {
+            path,
+            temp_path,
+            file: BufWriter::new(file),
+            len: 0,
+        })
+    }
+
+    /// Moves a fully written chunk into place.
+    pub async fn commit(&self, mut writer: CacheWriter) {
+        if writer.file.flush().await.is_err() {
+            return;
+        }
+        if tokio::fs::rename(&writer.temp_path, &writer.path).await.is_err() {
+            return;
+        }
+
+        let added = writer.len;
+        if self.size.fetch_add(added, Ordering::Relaxed) + added > self.max_bytes {
+            self.trim().await;
+        }
+    }
+
+    pub async fn remove(&self, key: &str) {
+        let path = self.entry_path(key);
+        if let Ok(metadata) = tokio::fs::metadata(&path).await {
+            if tokio::fs::remove_file(&path).await.is_ok() {
+                let _ = self.size.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |size| {
+                    Some(size.saturating_sub(metadata.len()))
+                });
+            }
+        }
+    }
+
+    /// Evicts the least recently used chunks until the cache is back under its limit.
+    async fn trim(&self) {
+        let Ok(_guard) = self.trimming.try_lock() else {
+            return;
+        };
+
+        let dir = self.dir.clone();
+        let target = (self.max_bytes / 100) * TRIM_TARGET_PERCENT;
+
+        if let Ok(size) = tokio::task::spawn_blocking(move || trim_to(&dir, target)).await {
+            self.size.store(size, Ordering::Relaxed);
+        }
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        let file_name: String = key
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+            .collect();
+        let shard: String = file_name.chars().take(2).collect();
+
+        self.dir.join(shard).join(format!("{}.chunk", file_name))
+    }
+}
+
+/// A chunk on its way into the cache. Dropping it without committing throws away
+/// what was written.
+pub struct CacheWriter {
+    path: PathBuf,
+    temp_path: PathBuf,
+    file: BufWriter<AsyncFile>,
+    len: u64,
+}
+
+impl CacheWriter {
+    pub async fn write(&mut self, data: &[u8]) -> std::io::Result<()> {
+        self.file.write_all(data).await?;
+        self.len += data.len() as u64;
+        Ok(())
+    }
+}
+
+impl Drop for CacheWriter {
+    fn drop(&mut self) {
+        // Already gone once the chunk has been committed.
+        let _ = fs::remove_file(&self.temp_path);
+    }
+}
+
+struct CacheEntry {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+fn cache_dir() -> Option<PathBuf> {
+    Some(dirs::home_dir()?.join("Solaris").join("ChunkCache"))
+}
+
+fn list_entries(dir: &Path) -> Vec<CacheEntry> {
+    let mut entries = Vec::new();
+
+    let Ok(shards) = fs::read_dir(dir) else {
+        return entries;
+    };
+
+    for shard in shards.flatten() {
+        let Ok(files) = fs::read_dir(shard.path()) else {
+            continue;
+        };
+
+        for file in files.flatten() {
+            let path = file.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("chunk") {
+                continue;
+            }
+
+            if let Ok(metadata) = file.metadata() {
+                entries.push(CacheEntry {
+                    path,
+                    size: metadata.len(),
+                    modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                });
+            }
+        }
+    }
+
+    entries
+}
+
+/// Deletes the oldest entries until at most `target` bytes remain, returning the size
+/// left behind.
+fn trim_to(dir: &Path, target: u64) -> u64 {
+    let mut entries = list_entries(dir);
+    let mut size: u64 = entries
+        .iter()
+        .map(|entry| entry.size)
+        .sum();
+
+    entries.sort_by_key(|entry| entry.modified);
+
+    for entry in entries {
+        if size <= target {
+            break;
+        }
+        if fs::remove_file(&entry.path).is_ok() {
+            size -= entry.size;
+        }
+    }
+
+    size
+}
+
+#[command]
+pub async fn get_chunk_cache_info() -> Result<ChunkCacheInfo, LauncherError> {
+    let dir = cache_dir().ok_or_else(|| {
+        LauncherError::not_found("Could not determine home directory")
+    })?;
+    let max_bytes = DownloadSettings::load().await.chunk_cache_max_bytes;
+
+    let scan_dir = dir.clone();
+    let entries = tokio::task
+        ::spawn_blocking(move || list_entries(&scan_dir)).await?;
+
+    Ok(ChunkCacheInfo {
+        path: dir.to_string_lossy().to_string(),
+        entries: entries.len(),
+        total_bytes: entries
+            .iter()
+            .map(|entry| entry.size)
+            .sum(),
+        max_bytes,
+    })
+}
+
+/// Empties the cache and returns how many bytes were freed.
+#[command]
+pub async fn clear_chunk_cache() -> Result<u64, LauncherError> {
+    let dir = cache_dir().ok_or_else(|| {
+        LauncherError::not_found("Could not determine home directory")
+    })?;
+
+    tokio::task::spawn_blocking(move || {
+        let freed: u64 = list_entries(&dir)
+            .iter()
+            .map(|entry| entry.size)
+            .sum();
+        match fs::remove_dir_all(&dir) {
+            Ok(()) => Ok(freed),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(LauncherError::from(e).prefixed("Failed to clear chunk cache")),
+        }
+    }).await?
+}
+
+/// Sets the cache size limit in bytes, evicting straight away if the cache is already
+/// bigger. Zero turns the cache off for future downloads.
+#[command]
+pub async fn set_chunk_cache_limit(max_bytes: u64) -> Result<u64, LauncherError> {
+    let mut settings = DownloadSettings::load().await;
+    settings.chunk_cache_max_bytes = max_bytes;
+    settings.save().await.context("Failed to save cache limit")?;
+
+    if max_bytes > 0 {
+        if let Some(dir) = cache_dir() {
+            let _ = tokio::task::spawn_blocking(move || trim_to(&dir, max_bytes)).await;
+        }
+    }
+
+    Ok(max_bytes)
+}
@@ -0,0 +1,215 @@
+use bytes::Bytes;
+use flate2::read::GzDecoder;
+use serde::{ Deserialize, Serialize };
+use sha2::{ Digest, Sha256 };
+use std::fs::{ self, File };
+use std::io::{ self, BufWriter, Read, Write };
+use std::path::{ Path, PathBuf };
+use std::sync::atomic::{ AtomicU64, Ordering };
+use tokio::fs::File as AsyncFile;
+use tokio::io::{ AsyncReadExt, AsyncWriteExt };
+use tokio::sync::mpsc;
+
+use super::checksum;
+use crate::error::{ LauncherError, ResultExt };
+
+/// Compressed pieces a download may queue up ahead of the decoder. Together with the
+/// fixed buffers below this caps what one chunk holds in memory, however large it is.
+const QUEUED_PIECES: usize = 16;
+const BUFFER_SIZE: usize = 256 * 1024;
+
+static STAGING_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// How chunks are compressed on the CDN. Manifests that don't say are gzip, which is
+/// all the launcher used to understand.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    #[default]
+    Gzip,
+    Zstd,
+    Brotli,
+    /// Stored uncompressed. Manifests may also call this `raw`.
+    #[serde(alias = "raw")]
+    None,
+}
+
+impl Compression {
+    fn decoder<'a>(self, reader: impl Read + 'a) -> io::Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            Self::Gzip => Box::new(GzDecoder::new(reader)),
+            Self::Zstd => Box::new(zstd::stream::read::Decoder::new(reader)?),
+            Self::Brotli => Box::new(brotli::Decompressor::new(reader, BUFFER_SIZE)),
+            Self::None => Box::new(reader),
+        })
+    }
+
+    /// Suffix for chunk cache keys, so the same chunk cached in two formats can't be
+    /// decoded with the wrong one. Gzip has none to keep existing cache entries valid.
+    pub fn cache_suffix(self) -> &'static str {
+        match self {
+            Self::Gzip => "",
+            Self::Zstd => ".zst",
+            Self::Brotli => ".br",
+            Self::None => ".raw",
+        }
+    }
+}
+
+/// A decompressed chunk waiting on disk for its turn to be appended to its file.
+/// Chunks are fetched in parallel but written in order, so each one is staged
+/// rather than held in memory. The staging file is removed when this is dropped.
+pub struct StagedChunk {
+    path: PathBuf,
+    len: u64,
+}
+
+impl StagedChunk {
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Appends the chunk to `output`, feeding it to `hasher` on the way.
+    pub async fn append_to(
+        &self,
+        output: &mut AsyncFile,
+        mut hasher: Option<&mut Sha256>
+    ) -> io::Result<()> {
+        let mut input = AsyncFile::open(&self.path).await?;
+        let mut buffer = vec![0u8; BUFFER_SIZE];
+
+        loop {
+            let read = input.read(&mut buffer).await?;
+            if read == 0 {
+                break;
+            }
+            if let Some(hasher) = hasher.as_deref_mut() {
+                hasher.update(&buffer[..read]);
+            }
+            output.write_all(&buffer[..read]).await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for StagedChunk {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// A fresh staging file name for `chunk_id` under `dir`. The same chunk can be in
+/// flight more than once, so names are never reused.
+pub fn staging_path(dir: &Path, chunk_id: i32) -> PathBuf {
+    let count = STAGING_COUNTER.fetch_add(1, Ordering::Relaxed);
+    dir.join(format!("{}-{}.staged", chunk_id, count))
+}
+
+/// Hands compressed pieces from the async download over to the decoder thread.
+/// Sending waits once `QUEUED_PIECES` are queued, so a slow disk slows the download
+/// down instead of piling data up in memory.
+pub fn piece_channel() -> (mpsc::Sender<Bytes>, PieceReader) {
+    let (sender, receiver) = mpsc::channel(QUEUED_PIECES);
+    (sender, PieceReader { receiver, current: Bytes::new() })
+}
+
+/// The decoder's end of `piece_channel`. Reads block, so it must only be used from
+/// a blocking task. Reaches end of file once the sender is dropped.
+pub struct PieceReader {
+    receiver: mpsc::Receiver<Bytes>,
+    current: Bytes,
+}
+
+impl Read for PieceReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.current.is_empty() {
+            match self.receiver.blocking_recv() {
+                Some(piece) => {
+                    self.current = piece;
+                }
+                None => {
+                    return Ok(0);
+                }
+            }
+        }
+
+        let len = buf.len().min(self.current.len());
+        buf[..len].copy_from_slice(&self.current[..len]);
+        self.current = self.current.slice(len..);
+        Ok(len)
+    }
+}
+
+/// Decompresses `compressed` into a staging file at `path`, checking the result
+/// against the chunk's manifest hash as it goes. Blocking.
+pub fn decode_chunk(
+    compressed: impl Read,
+    compression: Compression,
+    path: PathBuf,
+    chunk_id: i32,
+    expected_hash: Option<&str>
+) -> Result<StagedChunk, LauncherError> {
+    let corrupt = |e: io::Error| {
+        LauncherError::corrupt_data(format!("Failed to decompress chunk {}: {}", chunk_id, e))
+    };
+
+    let mut staged = StagedChunk { path, len: 0 };
+    let mut output = BufWriter::with_capacity(
+        BUFFER_SIZE,
+        File::create(&staged.path).context("Failed to create staging file")?
+    );
+    let mut decoder = compression.decoder(compressed).map_err(corrupt)?;
+    let mut hasher = expected_hash.map(|_| Sha256::new());
+    let mut buffer = vec![0u8; BUFFER_SIZE];
+
+    loop {
+        let read = decoder.read(&mut buffer).map_err(corrupt)?;
+        if read == 0 {
+            break;
+        }
+
+        if let Some(hasher) = hasher.as_mut() {
+            hasher.update(&buffer[..read]);
+        }
+        output.write_all(&buffer[..read]).context("Failed to write staging file")?;
+        staged.len += read as u64;
+    }
+
+    output.flush().context("Failed to write staging file")?;
+
+    if let (Some(expected_hash), Some(hasher)) = (expected_hash, hasher) {
+        let actual_hash = checksum::finalize_hex(hasher);
+        if !checksum::hashes_match(expected_hash, &actual_hash) {
+            return Err(
+                LauncherError::corrupt_data(
+                    format!(
+                        "Chunk {} failed hash verification: expected {}, got {}",
+                        chunk_id,
+                        expected_hash,
+                        actual_hash
+                    )
+                )
+            );
+        }
+    }
+
+    Ok(staged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_both_names_for_uncompressed_chunks() {
+        let parse = |json: &str| serde_json::from_str::<Compression>(json).unwrap();
+
+        assert_eq!(parse("\"none\""), Compression::None);
+        assert_eq!(parse("\"raw\""), Compression::None);
+        assert_eq!(parse("\"zstd\""), Compression::Zstd);
+        assert_eq!(parse("\"brotli\""), Compression::Brotli);
+        assert_eq!(parse("\"gzip\""), Compression::Gzip);
+        assert!(serde_json::from_str::<Compression>("\"lzma\"").is_err());
+    }
+}
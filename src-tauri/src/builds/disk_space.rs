@@ -0,0 +1,209 @@
+use reqwest::header::CONTENT_LENGTH;
+use serde::Serialize;
+use std::fmt;
+use std::path::{ Path, PathBuf };
+use std::time::Duration;
+use sysinfo::{ DiskExt, System, SystemExt };
+use tauri::command;
+
+use super::chunk_cache::ChunkCache;
+use super::download_manager::{ DEFAULT_CHUNK_WORKERS, ManifestFile, fetch_manifest };
+use super::network;
+use crate::error::{ LauncherError, ResultExt };
+
+const HEAD_TIMEOUT_SECS: u64 = 15;
+
+/// Free space on the volume holding `path`, as (mount point, available bytes). Works
+/// for paths that don't exist yet by looking at their closest existing parent. Returns
+/// None if the volume can't be identified.
+pub fn available_space(path: &Path) -> Option<(PathBuf, u64)> {
+    let existing = path.ancestors().find(|ancestor| ancestor.exists())?;
+    let target = strip_verbatim(existing.canonicalize().ok()?);
+
+    let mut system = System::new();
+    system.refresh_disks_list();
+
+    system
+        .disks()
+        .iter()
+        .filter(|disk| target.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| (disk.mount_point().to_path_buf(), disk.available_space()))
+}
+
+/// `canonicalize` returns `\\?\C:\...` on Windows, which never matches the plain `C:\`
+/// mount points sysinfo reports.
+fn strip_verbatim(path: PathBuf) -> PathBuf {
+    let path_str = path.to_string_lossy();
+    match path_str.strip_prefix(r"\\?\") {
+        Some(stripped) => PathBuf::from(stripped),
+        None => path,
+    }
+}
+
+/// Bytes a manifest install still has to write, given how much a previous attempt
+/// already left on disk, plus room for the largest file's `.part` to sit next to an
+/// existing copy while it is replaced, and for the decompressed chunks `workers`
+/// downloads keep staged while they wait their turn.
+pub fn manifest_requirement(
+    manifest: &ManifestFile,
+    completed_bytes: u64,
+    workers: usize
+) -> (u64, u64) {
+    let required = (manifest.size.max(0) as u64).saturating_sub(completed_bytes);
+    let largest_file = manifest.chunks
+        .iter()
+        .map(|chunked_file| chunked_file.file_size.max(0) as u64)
+        .max()
+        .unwrap_or(0);
+
+    // Chunks aren't sized in the manifest; a file's average chunk is close enough.
+    let largest_chunk = manifest.chunks
+        .iter()
+        .filter(|chunked_file| !chunked_file.chunks_ids.is_empty())
+        .map(|chunked_file| {
+            (chunked_file.file_size.max(0) as u64).div_ceil(chunked_file.chunks_ids.len() as u64)
+        })
+        .max()
+        .unwrap_or(0);
+    let staging = largest_chunk * ((workers as u64) + 1);
+
+    (required, largest_file + staging)
+}
+
+/// Bytes a direct download still needs. An archive that will be extracted is assumed to
+/// unpack to at least its own size, next to the archive itself.
+pub fn direct_requirement(remaining_bytes: u64, extract: bool) -> (u64, u64) {
+    (remaining_bytes, if extract { remaining_bytes } else { 0 })
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DiskSpaceReport {
+    path: String,
+    mount_point: Option<String>,
+    required_bytes: u64,
+    temp_overhead_bytes: u64,
+    available_bytes: Option<u64>,
+    sufficient: bool,
+}
+
+impl DiskSpaceReport {
+    pub fn new(path: &Path, required_bytes: u64, temp_overhead_bytes: u64) -> Self {
+        let space = available_space(path);
+        let total_needed = required_bytes.saturating_add(temp_overhead_bytes);
+
+        Self {
+            path: path.to_string_lossy().to_string(),
+            mount_point: space
+                .as_ref()
+                .map(|(mount_point, _)| mount_point.to_string_lossy().to_string()),
+            required_bytes,
+            temp_overhead_bytes,
+            available_bytes: space.as_ref().map(|(_, available)| *available),
+            // If the volume can't be identified, let the download try rather than block it.
+            sufficient: space.is_none_or(|(_, available)| available >= total_needed),
+        }
+    }
+
+    /// Fails with a `disk_full` error built from `InsufficientSpace` when the volume is
+    /// too small.
+    pub fn ensure_sufficient(self) -> Result<(), LauncherError> {
+        match self.available_bytes {
+            Some(available_bytes) if !self.sufficient =>
+                Err(
+                    (InsufficientSpace {
+                        path: self.path,
+                        required_bytes: self.required_bytes + self.temp_overhead_bytes,
+                        available_bytes,
+                    }).into()
+                ),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The destination volume can't hold the download. Sent to the frontend as the
+/// `download:insufficient_space` event alongside the usual `download:failed`.
+#[derive(Clone, Debug, Serialize)]
+pub struct InsufficientSpace {
+    pub path: String,
+    pub required_bytes: u64,
+    pub available_bytes: u64,
+}
+
+impl fmt::Display for InsufficientSpace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Not enough disk space for {}: {} required, {} available",
+            self.path,
+            format_bytes(self.required_bytes),
+            format_bytes(self.available_bytes)
+        )
+    }
+}
+
+impl std::error::Error for InsufficientSpace {}
+
+fn format_bytes(bytes: u64) -> String {
+    let units = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit_index = 0;
+
+    while size >= 1024.0 && unit_index < units.len() - 1 {
+        size /= 1024.0;
+        unit_index += 1;
+    }
+
+    format!("{:.2} {}", size, units[unit_index])
+}
+
+/// Reports whether `destination` has room for a build, sized from the manifest when
+/// `version` is given, otherwise from the `Content-Length` of `url`.
+#[command]
+pub async fn check_disk_space(
+    destination: String,
+    version: Option<String>,
+    url: Option<String>,
+    extract: Option<bool>
+) -> Result<DiskSpaceReport, LauncherError> {
+    let (required_bytes, temp_overhead_bytes) = if let Some(version) = version {
+        let manifest = fetch_manifest(&version).await?.data;
+        let (required_bytes, overhead_bytes) = manifest_requirement(
+            &manifest,
+            0,
+            DEFAULT_CHUNK_WORKERS
+        );
+        let cache_headroom = ChunkCache::headroom(Path::new(&destination)).await;
+        (required_bytes, overhead_bytes + cache_headroom)
+    } else if let Some(url) = url {
+        let response = network
+            ::client().await?
+            .head(&url)
+            .timeout(Duration::from_secs(HEAD_TIMEOUT_SECS))
+            .send().await
+            .context(format!("Failed to reach {}", url))?;
+
+        // Read the header directly: reqwest reports a HEAD response's empty body as length 0.
+        let content_length = response
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok())
+            .filter(|length| *length > 0)
+            .ok_or_else(|| {
+                LauncherError::unsupported("Server did not report the download size")
+            })?;
+
+        direct_requirement(content_length, extract.unwrap_or(false))
+    } else {
+        return Err(LauncherError::invalid_input("Either a version or a URL is required"));
+    };
+
+    let destination = PathBuf::from(destination);
+    tokio::task
+        ::spawn_blocking(move || {
+            DiskSpaceReport::new(&destination, required_bytes, temp_overhead_bytes)
+        }).await
+        .map_err(LauncherError::from)
+}
@@ -1,42 +1,279 @@
-use flate2::read::GzDecoder;
-use futures_util::StreamExt;
+use futures_util::stream::{ self, StreamExt };
 use indicatif::ProgressBar;
-use regex::Regex;
-use reqwest::Client;
+use reqwest::header::{ ACCEPT_RANGES, CONTENT_LENGTH, IF_RANGE, RANGE };
+use reqwest::{ Client, StatusCode };
+use serde::de::DeserializeOwned;
 use serde::{ Deserialize, Serialize };
+use sha2::{ Digest, Sha256 };
 use std::fs::{ self, File };
 use std::io::{ self, Read, Seek, SeekFrom, Write };
 use std::path::{ Path, PathBuf };
 use std::sync::Arc;
+use std::sync::atomic::{ AtomicU64, AtomicUsize, Ordering };
 use std::time::{ Duration, Instant };
-use tauri::{ AppHandle, Emitter, State, Window, command };
+use tauri::{ AppHandle, Emitter, Manager, State, Window, command };
 use tokio::fs::File as AsyncFile;
-use tokio::io::{ AsyncWriteExt, BufReader, BufWriter };
-use tokio::sync::Mutex;
+use tokio::io::{ AsyncSeekExt, AsyncWriteExt, BufReader, BufWriter };
+use tokio::sync::{ Mutex, OnceCell };
 use tokio::time::timeout;
 
+use super::checksum::{ self, ExpectedHash };
+use super::chunk_cache::ChunkCache;
+use super::decompress::{ self, Compression, StagedChunk };
+use super::disk_space::{ self, DiskSpaceReport };
+use super::epic_manifest::{ self, ChunkInfo, ChunkPart, EpicFile, EpicManifest, Guid };
+use super::extract::{ self, ArchiveKind };
+use super::journal::{ FileProgress, InstallJournal, ResumableInstall, unix_now };
+use super::network;
+use super::partial::PartialDownload;
+use super::queue::{ DownloadQueue, QueueEntry, QueuePosition };
+use super::response_cache::{ Cached, CachedResponse, ResponseCache };
+use super::state::{ DownloadSnapshot, DownloadState, DownloadStates };
+use super::settings::DownloadSettings;
+use super::throttle::RateLimiter;
+use super::version::BuildVersion;
+use crate::error::{ ErrorCode, LauncherError, ResultExt };
+
 const MAX_RETRIES: usize = 3;
 const RETRY_DELAY_MS: u64 = 1000;
 const UPDATE_INTERVAL_MS: u64 = 100;
+const POLL_INTERVAL_MS: u64 = 250;
 const REQUEST_TIMEOUT_SECS: u64 = 60;
-const BASE_URL: &str = "https://manifest.simplyblk.xyz";
+const JOURNAL_SAVE_INTERVAL_MS: u64 = 1000;
+pub(super) const DEFAULT_CHUNK_WORKERS: usize = 8;
+pub(super) const MAX_CHUNK_WORKERS: usize = 32;
+/// Failed or slow chunk responses in a row before moving on to the next mirror.
+const MIRROR_STRIKES: usize = 3;
+/// A mirror taking longer than this to answer a chunk request counts as a strike.
+const SLOW_MIRROR_MS: u64 = 5000;
+const VERSIONS_CACHE_KEY: &str = "versions";
+/// Direct downloads at least this big are split into ranges fetched side by side.
+const SEGMENT_MIN_BYTES: u64 = 32 * 1024 * 1024;
+const DEFAULT_CONNECTIONS: usize = 4;
+const MAX_CONNECTIONS: usize = 16;
+/// Times a game file is downloaded again after failing its checksum before giving up.
+const MAX_CHECKSUM_RETRIES: usize = 2;
+/// A segment saves its progress at least this often.
+const CHECKPOINT_BYTES: u64 = 16 * 1024 * 1024;
+/// A direct download with no data for this long drops the connection and asks again.
+const STALL_TIMEOUT: Duration = Duration::from_secs(30);
+/// Folder inside an install where decompressed chunks wait to be written out.
+const STAGING_DIR: &str = ".solaris-staging";
 
 pub struct DownloadManager {
     active_downloads: Mutex<Vec<String>>,
+    paused_downloads: Mutex<Vec<String>>,
     active_extractions: Mutex<Vec<String>>,
+    queue: Mutex<DownloadQueue>,
     download_speeds: Mutex<std::collections::HashMap<String, Vec<(Instant, u64)>>>,
+    global_rate_limit: RateLimiter,
+    rate_limits: Mutex<std::collections::HashMap<String, Arc<RateLimiter>>>,
+    states: Mutex<DownloadStates>,
 }
 
 impl DownloadManager {
     pub fn new() -> Self {
         Self {
             active_downloads: Mutex::new(Vec::new()),
+            paused_downloads: Mutex::new(Vec::new()),
             active_extractions: Mutex::new(Vec::new()),
+            queue: Mutex::new(DownloadQueue::new()),
             download_speeds: Mutex::new(std::collections::HashMap::new()),
+            global_rate_limit: RateLimiter::new(),
+            rate_limits: Mutex::new(std::collections::HashMap::new()),
+            states: Mutex::new(DownloadStates::new()),
+        }
+    }
+
+    /// Marks `build_id` as running. Returns false if it already is.
+    pub(super) async fn register(&self, build_id: &str) -> bool {
+        let mut active_downloads = self.active_downloads.lock().await;
+        if active_downloads.iter().any(|id| id == build_id) {
+            return false;
+        }
+        active_downloads.push(build_id.to_string());
+        true
+    }
+
+    /// Removes `build_id` from the running set. Returns false if `cancel_download`
+    /// already removed it.
+    pub(super) async fn unregister(&self, build_id: &str) -> bool {
+        let removed = {
+            let mut active_downloads = self.active_downloads.lock().await;
+            if let Some(index) = active_downloads.iter().position(|id| id == build_id) {
+                active_downloads.remove(index);
+                true
+            } else {
+                false
+            }
+        };
+        self.paused_downloads.lock().await.retain(|id| id != build_id);
+        self.queue.lock().await.remove(build_id);
+        self.rate_limits.lock().await.remove(build_id);
+        self.clear_speed_data(build_id).await;
+        removed
+    }
+
+    /// Puts `build_id` in the download queue behind everything of the same or higher
+    /// priority. `wait_for_turn` then holds it until a slot is free.
+    pub(super) async fn enqueue(&self, build_id: &str, priority: i32) {
+        self.queue.lock().await.enqueue(build_id, priority);
+    }
+
+    /// Waits in the download queue until `build_id` may start, emitting
+    /// `download:queued` whenever its position changes. Returns false if the build
+    /// was cancelled while waiting.
+    pub(super) async fn wait_for_turn(&self, window: &Window, build_id: &str) -> bool {
+        let mut last_position = None;
+
+        loop {
+            if !self.is_active(build_id).await {
+                self.queue.lock().await.remove(build_id);
+                return false;
+            }
+
+            let position = {
+                let mut queue = self.queue.lock().await;
+                if queue.try_start(build_id) {
+                    return true;
+                }
+                queue.position(build_id)
+            };
+
+            if position != last_position {
+                self.set_state(window, build_id, DownloadState::Queued { position }).await;
+
+                if let Some(position) = position {
+                    let _ = window.emit("download:queued", QueuePosition {
+                        build_id: build_id.to_string(),
+                        position,
+                    });
+                }
+                last_position = position;
+            }
+
+            tokio::time::sleep(Duration::from_millis(POLL_INTERVAL_MS)).await;
+        }
+    }
+
+    /// Starts tracking a new run of `build_id` and announces it with `download:state`.
+    pub(super) async fn begin_tracking(
+        &self,
+        window: &Window,
+        build_id: &str,
+        state: DownloadState
+    ) {
+        let snapshot = self.states.lock().await.begin(build_id, state);
+        let _ = window.emit("download:state", snapshot);
+    }
+
+    /// Moves `build_id` to `state`, emitting `download:state` if anything changed.
+    pub(super) async fn set_state(&self, window: &Window, build_id: &str, state: DownloadState) {
+        let snapshot = self.states.lock().await.transition(build_id, state);
+        if let Some(snapshot) = snapshot {
+            let _ = window.emit("download:state", snapshot);
+        }
+    }
+
+    async fn state_of(&self, build_id: &str) -> Option<DownloadState> {
+        self.states.lock().await.state(build_id).cloned()
+    }
+
+    /// Records the progress for `list_downloads` and sends it as `download:progress`.
+    async fn emit_progress(&self, window: &Window, progress: DownloadProgress) {
+        self.states.lock().await.record_progress(&progress.build_id, progress.clone());
+        let _ = window.emit("download:progress", progress);
+    }
+
+    pub(super) async fn record_step(&self, build_id: &str, percentage: f64) {
+        self.states.lock().await.record_step(build_id, percentage);
+    }
+
+    pub(super) async fn is_paused(&self, build_id: &str) -> bool {
+        let paused_downloads = self.paused_downloads.lock().await;
+        paused_downloads.iter().any(|id| id == build_id)
+    }
+
+    /// Blocks while `build_id` is paused, and after it resumes until it has a download
+    /// slot again. Returns false if the download was cancelled instead of resumed.
+    pub(super) async fn wait_while_paused(&self, window: &Window, build_id: &str) -> bool {
+        loop {
+            if !self.is_active(build_id).await {
+                return false;
+            }
+
+            if self.is_paused(build_id).await {
+                // Also covers builds paused while still queued, which only got their
+                // slot afterwards.
+                self.queue.lock().await.pause(build_id);
+            } else {
+                // The state changes under the queue lock so a worker that is still
+                // waiting can't report the build as queued after another started it.
+                let mut queue = self.queue.lock().await;
+                match queue.position(build_id) {
+                    None => {
+                        return true;
+                    }
+                    Some(_) if queue.try_start(build_id) => {
+                        self.set_state(window, build_id, DownloadState::Downloading).await;
+                        return true;
+                    }
+                    position => {
+                        self.set_state(window, build_id, DownloadState::Queued { position }).await;
+                    }
+                }
+            }
+
+            tokio::time::sleep(Duration::from_millis(POLL_INTERVAL_MS)).await;
+        }
+    }
+
+    /// Waits until `bytes` more may be downloaded for `download_id` under both its own
+    /// rate limit and the global one.
+    pub(crate) async fn throttle(&self, download_id: &str, bytes: u64) {
+        let own_limit = self.rate_limits.lock().await.get(download_id).cloned();
+        if let Some(own_limit) = own_limit {
+            own_limit.acquire(bytes).await;
+        }
+        self.global_rate_limit.acquire(bytes).await;
+    }
+
+    /// The limit currently in effect for `download_id`: the tighter of its own and the
+    /// global one, in bytes per second.
+    pub(crate) async fn rate_limit(&self, download_id: &str) -> Option<u64> {
+        let own_limit = self.rate_limits
+            .lock().await
+            .get(download_id)
+            .and_then(|limiter| limiter.limit());
+
+        match (own_limit, self.global_rate_limit.limit()) {
+            (Some(own), Some(global)) => Some(own.min(global)),
+            (own, global) => own.or(global),
+        }
+    }
+
+    /// Sets the limit for one download, or the global limit when `download_id` is None.
+    async fn set_rate_limit(&self, download_id: Option<&str>, bytes_per_sec: Option<u64>) {
+        let Some(download_id) = download_id else {
+            self.global_rate_limit.set_limit(bytes_per_sec);
+            return;
+        };
+
+        let mut rate_limits = self.rate_limits.lock().await;
+        match bytes_per_sec.filter(|limit| *limit > 0) {
+            Some(limit) => {
+                rate_limits
+                    .entry(download_id.to_string())
+                    .or_insert_with(|| Arc::new(RateLimiter::new()))
+                    .set_limit(Some(limit));
+            }
+            None => {
+                rate_limits.remove(download_id);
+            }
         }
     }
 
-    async fn update_speed_data(&self, build_id: &str, bytes: u64) -> f64 {
+    pub(super) async fn update_speed_data(&self, build_id: &str, bytes: u64) -> f64 {
         let mut speeds = self.download_speeds.lock().await;
         let speed_data = speeds.entry(build_id.to_string()).or_insert_with(Vec::new);
 
@@ -63,6 +300,16 @@ impl DownloadManager {
         }
     }
 
+    fn is_extracting_blocking(&self, build_id: &str) -> bool {
+        let active_extractions = self.active_extractions.blocking_lock();
+        active_extractions.iter().any(|id| id == build_id)
+    }
+
+    pub(super) async fn is_active(&self, build_id: &str) -> bool {
+        let active_downloads = self.active_downloads.lock().await;
+        active_downloads.iter().any(|id| id == build_id)
+    }
+
     async fn clear_speed_data(&self, build_id: &str) {
         let mut speeds = self.download_speeds.lock().await;
         speeds.remove(build_id);
@@ -77,6 +324,9 @@ pub struct DownloadProgress {
     total_bytes: u64,
     speed: f64,
     eta: String,
+    paused: bool,
+    /// Bytes per second this download is currently held to, if limited.
+    rate_limit: Option<u64>,
 }
 
 #[derive(Clone, Serialize)]
@@ -98,6 +348,18 @@ pub struct DownloadRequest {
     delete_after_extract: bool,
     use_manifest: Option<bool>,
     version: Option<String>,
+    chunk_workers: Option<usize>,
+    /// Connections a direct download may split itself across.
+    connections: Option<usize>,
+    /// Hash a direct download must match before it is kept, e.g. `sha256:<hex>`.
+    checksum: Option<ExpectedHash>,
+    priority: Option<i32>,
+    rate_limit: Option<u64>,
+    /// URL or local path of an Epic binary manifest to install from instead of a
+    /// Solaris manifest.
+    epic_manifest: Option<String>,
+    /// Where that manifest's chunks live, overriding the configured Epic chunk host.
+    chunk_base_url: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -109,13 +371,52 @@ pub struct DownloadResult {
 }
 
 #[derive(Serialize, Deserialize, Debug)]
-struct ChunkedFile {
+pub struct ChunkedFile {
     #[serde(rename = "ChunksIds")]
-    chunks_ids: Vec<i32>,
+    pub chunks_ids: Vec<i32>,
+    /// SHA-256 of each decompressed chunk, in the same order as `chunks_ids`.
+    /// Older manifests don't carry hashes, in which case nothing is verified.
+    #[serde(rename = "ChunksHashes", default)]
+    pub chunks_hashes: Vec<String>,
+    /// Compression of each chunk, in the same order as `chunks_ids`, for manifests
+    /// that mix formats. Chunks without an entry use the manifest's compression.
+    #[serde(rename = "ChunksCompression", default)]
+    pub chunks_compression: Vec<Compression>,
     #[serde(rename = "File")]
-    file: String,
+    pub file: String,
     #[serde(rename = "FileSize")]
-    file_size: i64,
+    pub file_size: i64,
+    /// SHA-256 of the finished file.
+    #[serde(rename = "FileHash", default)]
+    pub file_hash: Option<String>,
+}
+
+/// One entry of `versions.json`: either just the version string, or an object that
+/// can also say when the build was released.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum VersionListing {
+    Name(String),
+    Detailed {
+        version: String,
+        #[serde(default)]
+        release_date: Option<String>,
+    },
+}
+
+impl VersionListing {
+    pub fn version(&self) -> &str {
+        match self {
+            Self::Name(version) | Self::Detailed { version, .. } => version,
+        }
+    }
+
+    pub fn release_date(&self) -> Option<&str> {
+        match self {
+            Self::Name(_) => None,
+            Self::Detailed { release_date, .. } => release_date.as_deref(),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -126,6 +427,14 @@ pub struct ManifestFile {
     pub chunks: Vec<ChunkedFile>,
     #[serde(rename = "Size")]
     pub size: i64,
+    #[serde(rename = "Compression", default)]
+    pub compression: Compression,
+}
+
+impl ChunkedFile {
+    fn chunk_compression(&self, index: usize, default: Compression) -> Compression {
+        self.chunks_compression.get(index).copied().unwrap_or(default)
+    }
 }
 
 fn format_time(seconds: f64) -> String {
@@ -150,7 +459,7 @@ fn format_time(seconds: f64) -> String {
 pub async fn is_download_active(
     build_id: String,
     download_manager: State<'_, DownloadManager>
-) -> Result<bool, String> {
+) -> Result<bool, LauncherError> {
     let active_downloads = download_manager.active_downloads.lock().await;
     Ok(active_downloads.contains(&build_id))
 }
@@ -159,7 +468,7 @@ pub async fn is_download_active(
 pub async fn is_extraction_active(
     build_id: String,
     download_manager: State<'_, DownloadManager>
-) -> Result<bool, String> {
+) -> Result<bool, LauncherError> {
     let active_extractions = download_manager.active_extractions.lock().await;
     Ok(active_extractions.contains(&build_id))
 }
@@ -168,10 +477,11 @@ pub async fn is_extraction_active(
 pub async fn cancel_download(
     build_id: String,
     download_manager: State<'_, DownloadManager>
-) -> Result<bool, String> {
+) -> Result<bool, LauncherError> {
     let mut active_downloads = download_manager.active_downloads.lock().await;
     if let Some(index) = active_downloads.iter().position(|id| id == &build_id) {
         active_downloads.remove(index);
+        download_manager.paused_downloads.lock().await.retain(|id| id != &build_id);
         download_manager.clear_speed_data(&build_id).await;
         Ok(true)
     } else {
@@ -179,11 +489,143 @@ pub async fn cancel_download(
     }
 }
 
+#[command]
+pub async fn pause_download(
+    window: Window,
+    build_id: String,
+    download_manager: State<'_, DownloadManager>
+) -> Result<bool, LauncherError> {
+    if !download_manager.is_active(&build_id).await {
+        return Ok(false);
+    }
+
+    {
+        let mut paused_downloads = download_manager.paused_downloads.lock().await;
+        if paused_downloads.contains(&build_id) {
+            return Ok(false);
+        }
+        paused_downloads.push(build_id.clone());
+    }
+    download_manager.queue.lock().await.pause(&build_id);
+
+    download_manager.clear_speed_data(&build_id).await;
+    if download_manager.state_of(&build_id).await == Some(DownloadState::Downloading) {
+        download_manager.set_state(&window, &build_id, DownloadState::Paused).await;
+    }
+    let _ = window.emit("download:paused", build_id);
+    Ok(true)
+}
+
+#[command]
+pub async fn resume_download(
+    window: Window,
+    build_id: String,
+    download_manager: State<'_, DownloadManager>
+) -> Result<bool, LauncherError> {
+    let state = {
+        let mut paused_downloads = download_manager.paused_downloads.lock().await;
+        let Some(index) = paused_downloads.iter().position(|id| id == &build_id) else {
+            return Ok(false);
+        };
+
+        // Take a free slot back straight away, or wait in line for one.
+        let state = {
+            let mut queue = download_manager.queue.lock().await;
+            if !queue.resume(&build_id) || queue.try_start(&build_id) {
+                DownloadState::Downloading
+            } else {
+                DownloadState::Queued { position: queue.position(&build_id) }
+            }
+        };
+        paused_downloads.remove(index);
+        state
+    };
+
+    if download_manager.state_of(&build_id).await == Some(DownloadState::Paused) {
+        download_manager.set_state(&window, &build_id, state).await;
+    }
+    let _ = window.emit("download:resumed", build_id);
+    Ok(true)
+}
+
+#[command]
+pub async fn is_download_paused(
+    build_id: String,
+    download_manager: State<'_, DownloadManager>
+) -> Result<bool, LauncherError> {
+    Ok(download_manager.is_paused(&build_id).await)
+}
+
+/// Every build downloaded, verified or repaired since the launcher started, with its
+/// state and last progress, so the frontend can rebuild its view after a reload.
+#[command]
+pub async fn list_downloads(
+    download_manager: State<'_, DownloadManager>
+) -> Result<Vec<DownloadSnapshot>, LauncherError> {
+    Ok(download_manager.states.lock().await.list())
+}
+
+#[command]
+pub async fn get_download_queue(
+    download_manager: State<'_, DownloadManager>
+) -> Result<Vec<QueueEntry>, LauncherError> {
+    Ok(download_manager.queue.lock().await.entries())
+}
+
+#[command]
+pub async fn set_max_concurrent_downloads(
+    max: usize,
+    download_manager: State<'_, DownloadManager>
+) -> Result<usize, LauncherError> {
+    Ok(download_manager.queue.lock().await.set_max_concurrent(max))
+}
+
+#[command]
+pub async fn move_download_in_queue(
+    build_id: String,
+    position: usize,
+    download_manager: State<'_, DownloadManager>
+) -> Result<bool, LauncherError> {
+    Ok(download_manager.queue.lock().await.move_to(&build_id, position))
+}
+
+#[command]
+pub async fn prioritize_download(
+    build_id: String,
+    download_manager: State<'_, DownloadManager>
+) -> Result<bool, LauncherError> {
+    Ok(download_manager.queue.lock().await.move_to(&build_id, 1))
+}
+
+/// Limits download speed in bytes per second. With a `build_id` the limit applies to
+/// that download only (game files use `file:<destination path>`); without one it
+/// applies to all downloads together. `None` removes the limit.
+#[command]
+pub async fn set_download_rate_limit(
+    build_id: Option<String>,
+    bytes_per_sec: Option<u64>,
+    download_manager: State<'_, DownloadManager>
+) -> Result<(), LauncherError> {
+    download_manager.set_rate_limit(build_id.as_deref(), bytes_per_sec).await;
+    Ok(())
+}
+
+#[command]
+pub async fn get_download_rate_limit(
+    build_id: Option<String>,
+    download_manager: State<'_, DownloadManager>
+) -> Result<Option<u64>, LauncherError> {
+    match build_id {
+        Some(build_id) => Ok(download_manager.rate_limit(&build_id).await),
+        None => Ok(download_manager.global_rate_limit.limit()),
+    }
+}
+
 #[command]
 pub async fn cancel_extraction(
     build_id: String,
     download_manager: State<'_, DownloadManager>
-) -> Result<bool, String> {
+) -> Result<bool, LauncherError> {
     let mut active_extractions = download_manager.active_extractions.lock().await;
     if let Some(index) = active_extractions.iter().position(|id| id == &build_id) {
         active_extractions.remove(index);
@@ -194,66 +636,211 @@ pub async fn cancel_extraction(
 }
 
 #[command]
-pub async fn get_available_versions() -> Result<Vec<String>, String> {
-    let client = Client::new();
-    let versions_url = format!("{}/versions.json", "https://cdn.solarisfn.dev");
-
-    let response = client
-        .get(&versions_url)
-        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
-        .send().await
-        .map_err(|e| format!("Network error fetching versions: {}", e))?;
-
-    if response.status().is_success() {
-        response.json::<Vec<String>>().await.map_err(|e| format!("Failed to parse versions: {}", e))
-    } else {
-        Err(format!("Failed to fetch versions: HTTP {}", response.status()))
+pub async fn list_resumable_installs() -> Result<Vec<ResumableInstall>, LauncherError> {
+    let mut journals: Vec<InstallJournal> = InstallJournal::list().await
+        .into_iter()
+        .filter(|journal| Path::new(&journal.install_path).exists())
+        .collect();
+
+    journals.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+
+    Ok(
+        journals
+            .iter()
+            .map(|journal| journal.summary())
+            .collect()
+    )
+}
+
+#[command]
+pub async fn discard_resumable_install(
+    build_id: String,
+    download_manager: State<'_, DownloadManager>
+) -> Result<bool, LauncherError> {
+    if download_manager.is_active(&build_id).await {
+        return Err(LauncherError::already_running("Download is still in progress"));
+    }
+
+    match InstallJournal::load(&build_id).await {
+        Some(journal) => {
+            let _ = tokio::fs::remove_dir_all(&journal.install_path).await;
+            InstallJournal::remove(&build_id).await;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+#[command]
+pub async fn get_available_versions() -> Result<Cached<Vec<String>>, LauncherError> {
+    Ok(
+        fetch_versions().await?.map(|listings| {
+            listings
+                .into_iter()
+                .map(|listing| listing.version().to_string())
+                .collect()
+        })
+    )
+}
+
+/// The build list from the first version mirror that answers. Served from the
+/// on-disk cache, flagged stale, when none of them can be reached.
+pub(super) async fn fetch_versions() -> Result<Cached<Vec<VersionListing>>, LauncherError> {
+    let settings = DownloadSettings::load().await;
+    let client = network::client().await?;
+    let cache = ResponseCache::new(VERSIONS_CACHE_KEY);
+    let mut cached = cache.load().await;
+    let mut last_error = LauncherError::invalid_input("No version mirrors are configured");
+
+    for mirror in &settings.version_mirrors {
+        let versions_url = format!("{}/versions.json", mirror);
+
+        let mut request = client
+            .get(&versions_url)
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS));
+        if let Some(cached) = &cached {
+            request = cached.conditional(request);
+        }
+
+        match request.send().await {
+            Ok(response) if response.status() == StatusCode::NOT_MODIFIED => {
+                if let Some(cached) = cached.as_mut() {
+                    if let Some(versions) = cached.parse() {
+                        let fetched_at = cache.revalidated(cached).await;
+                        return Ok(Cached { data: versions, stale: false, fetched_at });
+                    }
+                }
+                last_error = LauncherError::corrupt_data(
+                    format!("{} answered 304 but the cached versions are unreadable", mirror)
+                );
+            }
+            Ok(response) if response.status().is_success() => {
+                let headers = response.headers().clone();
+                match read_json::<Vec<VersionListing>>(response).await {
+                    Ok((versions, body)) => {
+                        cache.store(&body, &headers).await;
+                        return Ok(Cached { data: versions, stale: false, fetched_at: unix_now() });
+                    }
+                    Err(e) => {
+                        last_error = e.prefixed(
+                            format!("Failed to parse versions from {}", mirror)
+                        );
+                    }
+                }
+            }
+            Ok(response) => {
+                last_error = LauncherError::http_status(
+                    response.status(),
+                    &versions_url,
+                    format!("Failed to fetch versions from {}: HTTP {}", mirror, response.status())
+                );
+            }
+            Err(e) => {
+                last_error = LauncherError::from(e).prefixed(
+                    format!("Network error fetching versions from {}", mirror)
+                );
+            }
+        }
     }
+
+    stale_or(cached, last_error)
 }
 
 #[command]
-pub async fn get_manifest_for_version(version: String) -> Result<ManifestFile, String> {
-    let re = Regex::new(r"Release-(\d+\.\d+)").map_err(|e| format!("Regex error: {}", e))?;
+pub async fn get_manifest_for_version(
+    version: String
+) -> Result<Cached<ManifestFile>, LauncherError> {
+    fetch_manifest(&version).await
+}
 
-    if let Some(caps) = re.captures(&version) {
-        let extracted_version = caps.get(1).unwrap().as_str();
+/// The manifest for `version` from the first manifest mirror that has it. Each mirror
+/// gets its own retries; a client error means it doesn't have the build, so move
+/// straight on to the next one. Manifests never change once released, so when no
+/// mirror can be reached the cached copy is served instead, flagged stale.
+pub(super) async fn fetch_manifest(version: &str) -> Result<Cached<ManifestFile>, LauncherError> {
+    let extracted_version = BuildVersion::parse(version)?.manifest_name();
 
-        let client = Client::new();
+    let settings = DownloadSettings::load().await;
+    let client = network::client().await?;
+    let cache = manifest_cache(&extracted_version);
+    let mut cached = cache.load().await;
+    let mut last_error = LauncherError::invalid_input("No manifest mirrors are configured");
+
+    for mirror in &settings.manifest_mirrors {
         let manifest_url = format!(
             "{}/{}/{}.manifest",
-            BASE_URL,
+            mirror,
             extracted_version,
             extracted_version
         );
-
         let mut retries = 0;
-        let mut last_error = String::new();
 
         while retries < MAX_RETRIES {
-            match
-                client
-                    .get(&manifest_url)
-                    .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
-                    .send().await
-            {
+            let mut request = client
+                .get(&manifest_url)
+                .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS));
+            if let Some(cached) = &cached {
+                request = cached.conditional(request);
+            }
+
+            match request.send().await {
                 Ok(response) => {
-                    if response.status().is_success() {
-                        return response
-                            .json::<ManifestFile>().await
-                            .map_err(|e| format!("Failed to parse manifest: {}", e));
+                    if response.status() == StatusCode::NOT_MODIFIED {
+                        if let Some(cached) = cached.as_mut() {
+                            if let Some(manifest) = cached.parse() {
+                                let fetched_at = cache.revalidated(cached).await;
+                                return Ok(Cached { data: manifest, stale: false, fetched_at });
+                            }
+                        }
+                        last_error = LauncherError::corrupt_data(
+                            format!("{} answered 304 but the cached manifest is unreadable", mirror)
+                        );
+                        break;
+                    } else if response.status().is_success() {
+                        let headers = response.headers().clone();
+                        match read_json::<ManifestFile>(response).await {
+                            Ok((manifest, body)) => {
+                                cache.store(&body, &headers).await;
+                                return Ok(Cached {
+                                    data: manifest,
+                                    stale: false,
+                                    fetched_at: unix_now(),
+                                });
+                            }
+                            Err(e) => {
+                                last_error = e.prefixed(
+                                    format!("Failed to parse manifest from {}", mirror)
+                                );
+                                break;
+                            }
+                        }
                     } else if response.status().is_server_error() {
-                        last_error = format!("Server error: HTTP {}", response.status());
+                        last_error = LauncherError::http_status(
+                            response.status(),
+                            &manifest_url,
+                            format!("Server error from {}: HTTP {}", mirror, response.status())
+                        );
                         retries += 1;
                         tokio::time::sleep(
                             Duration::from_millis(RETRY_DELAY_MS * (retries as u64))
                         ).await;
-                        continue;
                     } else {
-                        return Err(format!("Failed to fetch manifest: HTTP {}", response.status()));
+                        last_error = LauncherError::http_status(
+                            response.status(),
+                            &manifest_url,
+                            format!(
+                                "Failed to fetch manifest from {}: HTTP {}",
+                                mirror,
+                                response.status()
+                            )
+                        );
+                        break;
                     }
                 }
                 Err(e) => {
-                    last_error = format!("Network error: {}", e);
+                    last_error = LauncherError::from(e).prefixed(
+                        format!("Network error from {}", mirror)
+                    );
                     retries += 1;
                     tokio::time::sleep(
                         Duration::from_millis(RETRY_DELAY_MS * (retries as u64))
@@ -261,16 +848,44 @@ pub async fn get_manifest_for_version(version: String) -> Result<ManifestFile, S
                 }
             }
         }
+    }
 
-        Err(
-            format!(
-                "Failed to fetch manifest after {} retries. Last error: {}",
-                MAX_RETRIES,
-                last_error
-            )
-        )
-    } else {
-        Err("Version format is incorrect".to_string())
+    stale_or(cached, last_error.prefixed("Failed to fetch manifest from any mirror. Last error"))
+}
+
+/// The last saved manifest for `version`, without touching the network. Always stale.
+pub(super) async fn cached_manifest(version: &str) -> Option<Cached<ManifestFile>> {
+    let extracted_version = BuildVersion::parse(version).ok()?.manifest_name();
+    let cached = manifest_cache(&extracted_version).load().await?;
+
+    Some(Cached {
+        data: cached.parse()?,
+        stale: true,
+        fetched_at: cached.fetched_at(),
+    })
+}
+
+fn manifest_cache(release: &str) -> ResponseCache {
+    ResponseCache::new(&format!("manifest-{}", release))
+}
+
+/// Reads a JSON body, returning the raw bytes as well so they can be cached.
+async fn read_json<T: DeserializeOwned>(
+    response: reqwest::Response
+) -> Result<(T, Vec<u8>), LauncherError> {
+    let body = response.bytes().await?.to_vec();
+    let value = serde_json::from_slice(&body)?;
+    Ok((value, body))
+}
+
+/// Falls back to the cached copy, marked stale, when no mirror could provide a fresh one.
+fn stale_or<T: DeserializeOwned>(
+    cached: Option<CachedResponse>,
+    error: LauncherError
+) -> Result<Cached<T>, LauncherError> {
+    match cached.as_ref().and_then(|cached| Some((cached.parse()?, cached.fetched_at()))) {
+        Some((data, fetched_at)) => Ok(Cached { data, stale: true, fetched_at }),
+        None => Err(error),
     }
 }
 
@@ -279,45 +894,109 @@ pub async fn download_build(
     window: Window,
     request: DownloadRequest,
     download_manager: State<'_, DownloadManager>
-) -> Result<DownloadResult, String> {
+) -> Result<DownloadResult, LauncherError> {
     let build_id = request.build_id.clone();
 
-    {
-        let active_downloads = download_manager.active_downloads.lock().await;
-        if active_downloads.contains(&build_id) {
-            return Err("Download already in progress".into());
-        }
-    }
-
-    {
-        let mut active_downloads = download_manager.active_downloads.lock().await;
-        active_downloads.push(build_id.clone());
-    }
-
     let dest_path = Path::new(&request.destination);
     if let Some(parent) = dest_path.parent() {
         if !parent.exists() {
             std::fs
                 ::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create destination directory: {}", e))?;
+                .context("Failed to create destination directory")?;
         }
     }
 
+    if !download_manager.register(&build_id).await {
+        return Err(LauncherError::already_running("Download already in progress"));
+    }
+
+    download_manager.begin_tracking(
+        &window,
+        &build_id,
+        DownloadState::Queued { position: None }
+    ).await;
+
+    if request.rate_limit.is_some() {
+        download_manager.set_rate_limit(Some(&build_id), request.rate_limit).await;
+    }
+
+    download_manager.enqueue(&build_id, request.priority.unwrap_or(0)).await;
+
+    if !download_manager.wait_for_turn(&window, &build_id).await {
+        download_manager.unregister(&build_id).await;
+        download_manager.set_state(&window, &build_id, DownloadState::Failed {
+            reason: "Download cancelled".into(),
+        }).await;
+        let _ = window.emit("download:failed", build_id);
+        return Err(LauncherError::cancelled("Download cancelled"));
+    }
+
+    let running_state = if download_manager.is_paused(&build_id).await {
+        DownloadState::Paused
+    } else {
+        DownloadState::Downloading
+    };
+    download_manager.set_state(&window, &build_id, running_state).await;
+
+    let _ = window.emit("download:started", build_id.clone());
+
     let temp_dest = format!("{}.download", request.destination);
 
-    let use_manifest = request.use_manifest.unwrap_or(false);
+    let use_manifest = request.use_manifest.unwrap_or(false) || request.epic_manifest.is_some();
+    let chunk_workers = request.chunk_workers
+        .unwrap_or(DEFAULT_CHUNK_WORKERS)
+        .clamp(1, MAX_CHUNK_WORKERS);
+
+    let download_result = if let Some(manifest_source) = request.epic_manifest.clone() {
+        let mut journal = InstallJournal::open_or_create(
+            &build_id,
+            request.version.as_deref().unwrap_or(&manifest_source),
+            &request.destination,
+            &temp_dest
+        ).await;
+
+        let result = download_epic_manifest(
+            window.clone(),
+            build_id.clone(),
+            &manifest_source,
+            request.chunk_base_url.clone(),
+            &temp_dest,
+            chunk_workers,
+            &mut journal,
+            &download_manager
+        ).await;
+
+        if result.is_err() {
+            let _ = journal.save().await;
+        }
 
-    let download_result = if use_manifest {
+        result
+    } else if use_manifest {
         if let Some(version) = request.version.clone() {
-            download_manifest(
+            let mut journal = InstallJournal::open_or_create(
+                &build_id,
+                &version,
+                &request.destination,
+                &temp_dest
+            ).await;
+
+            let result = download_manifest(
                 window.clone(),
                 build_id.clone(),
                 &version,
                 &temp_dest,
+                chunk_workers,
+                &mut journal,
                 &download_manager
-            ).await
+            ).await;
+
+            if result.is_err() {
+                let _ = journal.save().await;
+            }
+
+            result
         } else {
-            Err("Version is required for manifest-based download".into())
+            Err(LauncherError::invalid_input("Version is required for manifest-based download"))
         }
     } else {
         download_file(
@@ -325,370 +1004,1983 @@ pub async fn download_build(
             build_id.clone(),
             &request.url,
             &temp_dest,
+            request.extract,
+            request.connections.unwrap_or(DEFAULT_CONNECTIONS).clamp(1, MAX_CONNECTIONS),
             &download_manager
         ).await
     };
 
-    {
-        let mut active_downloads = download_manager.active_downloads.lock().await;
-        if let Some(index) = active_downloads.iter().position(|id| id == &build_id) {
-            active_downloads.remove(index);
-        }
-        download_manager.clear_speed_data(&build_id).await;
-    }
+    // `cancel_download` removes the id itself, so a missing entry means the user cancelled.
+    let cancelled = !download_manager.unregister(&build_id).await;
 
-    match download_result {
-        Ok(_) => {
-            let file_metadata = fs
-                ::metadata(&temp_dest)
-                .map_err(|e| format!("Failed to verify downloaded file: {}", e))?;
+    let outcome: Result<DownloadResult, LauncherError> = async {
+        match download_result {
+            Ok(_) => {
+                if use_manifest {
+                    InstallJournal::remove(&build_id).await;
+                }
 
-            if file_metadata.len() == 0 && !use_manifest {
-                let _ = fs::remove_file(&temp_dest);
-                return Err("Downloaded file is empty. The URL may be invalid.".into());
-            }
+                let file_metadata = fs
+                    ::metadata(&temp_dest)
+                    .context("Failed to verify downloaded file")?;
 
-            if Path::new(&request.destination).exists() {
-                let _ = fs::remove_file(&request.destination);
-            }
+                if file_metadata.len() == 0 && !use_manifest {
+                    let _ = fs::remove_file(&temp_dest);
+                    return Err(
+                        LauncherError::corrupt_data(
+                            "Downloaded file is empty. The URL may be invalid."
+                        )
+                    );
+                }
 
-            fs
-                ::rename(&temp_dest, &request.destination)
-                .map_err(|e| format!("Failed to finalize download: {}", e))?;
+                if let Some(checksum) = request.checksum.as_ref().filter(|_| !use_manifest) {
+                    // A wrong file is no use to resume from, so the retry starts over.
+                    if let Err(e) = checksum.verify(Path::new(&temp_dest)).await {
+                        let _ = fs::remove_file(&temp_dest);
+                        return Err(e);
+                    }
+                }
 
-            Ok(DownloadResult {
-                success: true,
-                message: "Download completed successfully".into(),
-                path: Some(request.destination),
-                extracted_path: None,
-            })
-        }
-        Err(e) => {
-            let _ = std::fs::remove_file(&temp_dest);
-            let _ = window.emit("download:failed", build_id);
-            Err(e.to_string())
-        }
-    }
-}
+                if Path::new(&request.destination).exists() {
+                    let _ = fs::remove_file(&request.destination);
+                }
 
-async fn download_manifest(
-    window: Window,
-    build_id: String,
-    version: &str,
-    install_path: &str,
-    download_manager: &State<'_, DownloadManager>
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let manifest = match get_manifest_for_version(version.to_string()).await {
-        Ok(manifest) => manifest,
+                fs
+                    ::rename(&temp_dest, &request.destination)
+                    .context("Failed to finalize download")?;
+
+                // Only now is the build actually in place.
+                let _ = window.emit("download:completed", build_id.clone());
+
+                let extracted_path = if request.extract && !use_manifest {
+                    download_manager.set_state(&window, &build_id, DownloadState::Extracting).await;
+
+                    let extracted = extract_download(
+                        &window,
+                        &build_id,
+                        Path::new(&request.destination),
+                        &download_manager
+                    ).await?;
+
+                    if extracted.is_some() && request.delete_after_extract {
+                        let _ = fs::remove_file(&request.destination);
+                    }
+
+                    extracted.map(|extracted| extracted.to_string_lossy().to_string())
+                } else {
+                    None
+                };
+
+                Ok(DownloadResult {
+                    success: true,
+                    message: "Download completed successfully".into(),
+                    path: Some(request.destination.clone()),
+                    extracted_path,
+                })
+            }
+            Err(e) => {
+                if !use_manifest {
+                    // Failed direct downloads keep their partial file to resume from.
+                    if cancelled {
+                        let _ = std::fs::remove_file(&temp_dest);
+                        PartialDownload::remove(&temp_dest).await;
+                    }
+                } else if cancelled {
+                    // Failed manifest installs stay resumable; only an explicit cancel
+                    // discards them.
+                    let _ = tokio::fs::remove_dir_all(&temp_dest).await;
+                    InstallJournal::remove(&build_id).await;
+                }
+                if e.code == ErrorCode::DiskFull && e.context.contains_key("required_bytes") {
+                    let _ = window.emit("download:insufficient_space", e.context.clone());
+                }
+                let _ = window.emit("download:failed", build_id.clone());
+                Err(e)
+            }
+        }
+    }.await;
+
+    match &outcome {
+        Ok(_) => {
+            download_manager.set_state(&window, &build_id, DownloadState::Completed).await;
+        }
         Err(e) => {
-            return Err(format!("Failed to get manifest: {}", e).into());
+            download_manager.set_state(&window, &build_id, DownloadState::Failed {
+                reason: e.message.clone(),
+            }).await;
+        }
+    }
+
+    outcome
+}
+
+/// Downloads a launcher game file such as a pak or signature to `dest`. It is tracked,
+/// paused and cancelled like a build under the id `file:<dest>`, but skips the
+/// download queue since the game can't start without it. The file is written
+/// beside `dest` and only moved over the old copy once its size and `hash` check out,
+/// so a failed download leaves a working file untouched.
+#[command]
+pub async fn download_game_file(
+    window: Window,
+    url: String,
+    dest: String,
+    hash: Option<ExpectedHash>,
+    download_manager: State<'_, DownloadManager>
+) -> Result<DownloadResult, LauncherError> {
+    let dest_path = Path::new(&dest);
+    if let Some(parent) = dest_path.parent() {
+        fs::create_dir_all(parent).context("Failed to create directories")?;
+    }
+
+    // Namespaced so it can't collide with a build id, and keyed on the whole path so
+    // files sharing a name in different folders stay apart.
+    let file_id = format!("file:{}", dest);
+
+    if !download_manager.register(&file_id).await {
+        return Err(LauncherError::already_running("Download already in progress"));
+    }
+
+    download_manager.begin_tracking(&window, &file_id, DownloadState::Downloading).await;
+    let _ = window.emit("download:started", file_id.clone());
+
+    let temp_dest = format!("{}.download", dest);
+
+    let download_result = fetch_game_file(
+        &window,
+        &file_id,
+        &url,
+        &temp_dest,
+        hash.as_ref(),
+        &download_manager
+    ).await.and_then(|()| {
+        // Replaces the old file in one step, so it is never missing or half written.
+        fs::rename(&temp_dest, dest_path).context("Failed to replace old file")
+    });
+
+    let cancelled = !download_manager.unregister(&file_id).await;
+
+    match download_result {
+        Ok(()) => {
+            let _ = window.emit("download:completed", file_id.clone());
+            download_manager.set_state(&window, &file_id, DownloadState::Completed).await;
+
+            Ok(DownloadResult {
+                success: true,
+                message: "Download completed successfully".into(),
+                path: Some(dest),
+                extracted_path: None,
+            })
+        }
+        Err(e) => {
+            // Like direct builds, a failed download keeps its partial file to resume from.
+            if cancelled {
+                let _ = fs::remove_file(&temp_dest);
+                PartialDownload::remove(&temp_dest).await;
+            }
+            download_manager.set_state(&window, &file_id, DownloadState::Failed {
+                reason: e.message.clone(),
+            }).await;
+            let _ = window.emit("download:failed", file_id);
+            Err(e)
+        }
+    }
+}
+
+/// Downloads `url` to `temp_dest` and checks it against `hash`, starting over up to
+/// `MAX_CHECKSUM_RETRIES` times when it doesn't match.
+async fn fetch_game_file(
+    window: &Window,
+    file_id: &str,
+    url: &str,
+    temp_dest: &str,
+    hash: Option<&ExpectedHash>,
+    download_manager: &State<'_, DownloadManager>
+) -> Result<(), LauncherError> {
+    let mut checksum_failures = 0;
+
+    loop {
+        download_file(
+            window.clone(),
+            file_id.to_string(),
+            url,
+            temp_dest,
+            false,
+            DEFAULT_CONNECTIONS,
+            download_manager
+        ).await?;
+
+        let hash = match hash {
+            Some(hash) => hash,
+            None => {
+                return Ok(());
+            }
+        };
+
+        match hash.verify(Path::new(temp_dest)).await {
+            Ok(()) => {
+                return Ok(());
+            }
+            Err(e) if e.code == ErrorCode::ChecksumMismatch => {
+                // A wrong file is no use to resume from, so the next attempt starts over.
+                let _ = fs::remove_file(temp_dest);
+                checksum_failures += 1;
+                if checksum_failures > MAX_CHECKSUM_RETRIES {
+                    return Err(e);
+                }
+            }
+            Err(e) => {
+                return Err(e);
+            }
+        }
+    }
+}
+
+/// Unpacks a finished direct download next to it, reporting `extraction:progress` and
+/// stopping if `cancel_extraction` is called for the build. A download that isn't an
+/// archive is left as it is and gives None.
+async fn extract_download(
+    window: &Window,
+    build_id: &str,
+    archive_path: &Path,
+    download_manager: &State<'_, DownloadManager>
+) -> Result<Option<PathBuf>, LauncherError> {
+    let kind = match ArchiveKind::detect(archive_path) {
+        Some(kind) => kind,
+        None => {
+            log::info!("{} is not an archive, skipping extraction", archive_path.display());
+            return Ok(None);
         }
     };
+    let target = kind.target_dir(archive_path);
+    let target_existed = target.exists();
 
-    let total_size = manifest.size;
-    let mut completed_size: i64 = 0;
-    let start_time = std::time::Instant::now();
-    let mut last_update = std::time::Instant::now();
+    {
+        let mut active_extractions = download_manager.active_extractions.lock().await;
+        if active_extractions.iter().any(|id| id == build_id) {
+            return Err(LauncherError::already_running("Extraction already in progress"));
+        }
+        active_extractions.push(build_id.to_string());
+    }
 
-    let client = Client::builder()
-        .pool_max_idle_per_host(20)
+    let task_window = window.clone();
+    let task_build_id = build_id.to_string();
+    let task_archive = archive_path.to_path_buf();
+    let task_target = target.clone();
+
+    let result = tokio::task::spawn_blocking(move || {
+        let download_manager = task_window.state::<DownloadManager>();
+        let start_time = Instant::now();
+        let mut last_update = Instant::now();
+
+        extract::extract_archive(kind, &task_archive, &task_target, |step| {
+            if !download_manager.is_extracting_blocking(&task_build_id) {
+                return false;
+            }
+            download_manager.states
+                .blocking_lock()
+                .record_step(&task_build_id, step.fraction * 100.0);
+
+            if
+                last_update.elapsed().as_millis() > (UPDATE_INTERVAL_MS as u128) ||
+                step.fraction >= 1.0
+            {
+                let elapsed = start_time.elapsed().as_secs_f64();
+                let eta_seconds = if step.fraction > 0.0 {
+                    (elapsed * (1.0 - step.fraction)) / step.fraction
+                } else {
+                    f64::INFINITY
+                };
+
+                let _ = task_window.emit("extraction:progress", ExtractionProgress {
+                    build_id: task_build_id.clone(),
+                    percentage: step.fraction * 100.0,
+                    current_file: step.current_file.to_string(),
+                    total_files: step.total_files,
+                    processed_files: step.processed_files,
+                    eta: format_time(eta_seconds),
+                });
+
+                last_update = Instant::now();
+            }
+
+            true
+        })
+    }).await?;
+
+    {
+        let mut active_extractions = download_manager.active_extractions.lock().await;
+        active_extractions.retain(|id| id != build_id);
+    }
+
+    match result {
+        Ok(()) => {
+            let _ = window.emit("extraction:completed", build_id.to_string());
+            Ok(Some(target))
+        }
+        Err(e) => {
+            if !target_existed {
+                let _ = tokio::fs::remove_dir_all(&target).await;
+            }
+            let _ = window.emit("extraction:failed", build_id.to_string());
+            Err(e)
+        }
+    }
+}
+
+async fn download_manifest(
+    window: Window,
+    build_id: String,
+    version: &str,
+    install_path: &str,
+    chunk_workers: usize,
+    journal: &mut InstallJournal,
+    download_manager: &State<'_, DownloadManager>
+) -> Result<(), LauncherError> {
+    let manifest = fetch_manifest(version).await.context("Failed to get manifest")?.data;
+
+    let (required_bytes, overhead_bytes) = disk_space::manifest_requirement(
+        &manifest,
+        journal.completed_bytes,
+        chunk_workers
+    );
+    let cache_headroom = ChunkCache::headroom(Path::new(install_path)).await;
+    ensure_disk_space(
+        Path::new(install_path),
+        required_bytes,
+        overhead_bytes + cache_headroom
+    ).await?;
+
+    let source = ChunkSource::new(version, manifest.compression, chunk_workers).await?;
+
+    let base_path = Path::new(install_path);
+    if let Some(parent) = base_path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    let total_size = manifest.size as u64;
+    journal.total_bytes = total_size;
+
+    let files: Vec<&ChunkedFile> = manifest.chunks.iter().collect();
+    install_chunked_files(
+        &window,
+        &build_id,
+        &files,
+        base_path,
+        &source,
+        journal,
+        download_manager
+    ).await?;
+
+    download_manager.emit_progress(&window, DownloadProgress {
+        build_id: build_id.clone(),
+        percentage: 100.0,
+        downloaded_bytes: total_size,
+        total_bytes: total_size,
+        speed: 0.0,
+        eta: "0s".to_string(),
+        paused: false,
+        rate_limit: download_manager.rate_limit(&build_id).await,
+    }).await;
+
+    Ok(())
+}
+
+/// Installs a build from an Epic binary manifest. Chunks are fetched from
+/// `chunk_base_url`, else the configured Epic chunk host, else the folder the manifest
+/// itself was downloaded from, which is where Epic's CDN keeps them.
+async fn download_epic_manifest(
+    window: Window,
+    build_id: String,
+    manifest_source: &str,
+    chunk_base_url: Option<String>,
+    install_path: &str,
+    chunk_workers: usize,
+    journal: &mut InstallJournal,
+    download_manager: &State<'_, DownloadManager>
+) -> Result<(), LauncherError> {
+    let manifest = load_epic_manifest(manifest_source).await?;
+
+    let configured_url = DownloadSettings::load().await.epic_chunk_base_url;
+    let chunk_base_url = match chunk_base_url.or(configured_url) {
+        Some(url) => url.trim().trim_end_matches('/').to_string(),
+        None if is_remote(manifest_source) => {
+            let manifest_url = manifest_source.split('?').next().unwrap_or(manifest_source);
+            manifest_url
+                .rsplit_once('/')
+                .map(|(base, _)| base.to_string())
+                .unwrap_or_default()
+        }
+        None => {
+            return Err(
+                LauncherError::invalid_input("No chunk host is configured for Epic manifests")
+            );
+        }
+    };
+
+    let total_size = manifest.total_size();
+    let largest_file = manifest.files.iter().map(EpicFile::size).max().unwrap_or(0);
+    ensure_disk_space(
+        Path::new(install_path),
+        total_size.saturating_sub(journal.completed_bytes),
+        largest_file
+    ).await?;
+
+    let client = network
+        ::client_builder().await?
+        .pool_max_idle_per_host(chunk_workers.max(20))
         .pool_idle_timeout(std::time::Duration::from_secs(30))
         .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS))
         .connect_timeout(std::time::Duration::from_secs(10))
         .build()?;
 
-    let re = Regex::new(r"Release-(\d+\.\d+)")?;
-    let extracted_version = match re.captures(version) {
-        Some(caps) => caps.get(1).unwrap().as_str().to_string(),
-        None => {
-            return Err("Version extraction failed".into());
+    let source = EpicChunkSource {
+        manifest,
+        base_url: chunk_base_url,
+        client,
+        cache: ChunkCache::open().await,
+    };
+
+    let base_path = Path::new(install_path);
+    tokio::fs::create_dir_all(base_path).await?;
+
+    journal.total_bytes = total_size;
+
+    install_epic_files(
+        &window,
+        &build_id,
+        &source,
+        base_path,
+        chunk_workers,
+        journal,
+        download_manager
+    ).await?;
+
+    download_manager.emit_progress(&window, DownloadProgress {
+        build_id: build_id.clone(),
+        percentage: 100.0,
+        downloaded_bytes: total_size,
+        total_bytes: total_size,
+        speed: 0.0,
+        eta: "0s".to_string(),
+        paused: false,
+        rate_limit: download_manager.rate_limit(&build_id).await,
+    }).await;
+
+    Ok(())
+}
+
+fn is_remote(source: &str) -> bool {
+    source.starts_with("https://") || source.starts_with("http://")
+}
+
+/// Reads an Epic binary manifest from a URL or a local file.
+async fn load_epic_manifest(source: &str) -> Result<EpicManifest, LauncherError> {
+    let data = if is_remote(source) {
+        let client = network::client().await?;
+        request_with_retries(&client, source, 0, None).await?
+            .bytes().await
+            .context("Failed to download Epic manifest")?
+            .to_vec()
+    } else {
+        tokio::fs::read(source).await.context(format!("Failed to read Epic manifest {}", source))?
+    };
+
+    tokio::task
+        ::spawn_blocking(move || EpicManifest::parse(&data)).await
+        .context("Failed to parse Epic manifest")?
+}
+
+/// Writes every file of an Epic manifest under `base_path`, skipping the ones `journal`
+/// has already finished. Chunk parts are fetched `workers` at a time and written in
+/// order. A file that was cut short starts over, since parts don't line up with what
+/// a `.part` file can be trusted to hold.
+async fn install_epic_files(
+    window: &Window,
+    build_id: &str,
+    source: &EpicChunkSource,
+    base_path: &Path,
+    workers: usize,
+    journal: &mut InstallJournal,
+    download_manager: &DownloadManager
+) -> Result<(), LauncherError> {
+    let total_size = journal.total_bytes;
+    let mut last_update = Instant::now();
+    let mut last_journal_save = Instant::now();
+
+    let mut completed_size: u64 = 0;
+    let mut pending = Vec::new();
+    for file in &source.manifest.files {
+        if journal.completed_files.contains(&file.name) && base_path.join(&file.name).exists() {
+            completed_size += file.size();
+        } else {
+            journal.completed_files.remove(&file.name);
+            pending.push(file);
+        }
+    }
+
+    journal.current_file = None;
+    journal.completed_bytes = completed_size;
+    let _ = journal.save().await;
+
+    let shared_chunks = SharedChunks::new(pending.iter().flat_map(|file| &file.parts));
+    let shared_chunks = &shared_chunks;
+
+    let mut part_stream = stream
+        ::iter(pending.iter().flat_map(|file| file.parts.iter().map(move |part| (*file, part))))
+        .map(|(file, part)| async move {
+            if !download_manager.wait_while_paused(window, build_id).await {
+                return Err(LauncherError::cancelled("Download cancelled"));
+            }
+
+            shared_chunks.part(source, part, build_id, download_manager).await.context(&file.name)
+        })
+        .buffered(workers);
+
+    for &file in &pending {
+        let file_path = base_path.join(&file.name);
+        if let Some(parent) = file_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let temp_file_path = part_path(&file_path);
+        let mut output_file = AsyncFile::create(&temp_file_path).await?;
+        let mut hasher = epic_manifest::FileHasher::default();
+
+        for _ in &file.parts {
+            if !download_manager.is_active(build_id).await {
+                output_file.flush().await?;
+                return Err(LauncherError::cancelled("Download cancelled"));
+            }
+
+            if download_manager.is_paused(build_id).await {
+                output_file.flush().await?;
+                let _ = journal.save().await;
+                last_journal_save = Instant::now();
+
+                report_paused(window, build_id, completed_size, total_size, download_manager).await;
+            }
+
+            let data = match part_stream.next().await {
+                Some(Ok(data)) => data,
+                Some(Err(e)) => {
+                    output_file.flush().await?;
+                    return Err(e);
+                }
+                None => {
+                    output_file.flush().await?;
+                    return Err(LauncherError::internal("Chunk stream ended unexpectedly"));
+                }
+            };
+
+            output_file.write_all(&data).await?;
+            hasher.update(&data);
+
+            completed_size += data.len() as u64;
+            journal.completed_bytes = completed_size;
+
+            if last_journal_save.elapsed().as_millis() > (JOURNAL_SAVE_INTERVAL_MS as u128) {
+                let _ = journal.save().await;
+                last_journal_save = Instant::now();
+            }
+
+            report_progress(
+                window,
+                build_id,
+                completed_size,
+                total_size,
+                &mut last_update,
+                download_manager
+            ).await;
+        }
+
+        output_file.flush().await?;
+        drop(output_file);
+
+        if !hasher.matches(file) {
+            let _ = tokio::fs::remove_file(&temp_file_path).await;
+            return Err(
+                LauncherError::corrupt_data(
+                    format!("{} failed hash verification", file.name)
+                ).with_context("file", file.name.as_str())
+            );
+        }
+
+        if file_path.exists() {
+            tokio::fs::remove_file(&file_path).await?;
+        }
+
+        tokio::fs::rename(&temp_file_path, &file_path).await?;
+
+        journal.completed_files.insert(file.name.clone());
+    }
+
+    Ok(())
+}
+
+/// Decoded chunks that parts still waiting to be written need. A chunk several parts
+/// share is downloaded and checked once, whether or not those parts are in flight
+/// together, and dropped as soon as its last part has its data.
+struct SharedChunks {
+    slots: Mutex<std::collections::HashMap<Guid, SharedChunk>>,
+}
+
+struct SharedChunk {
+    data: Arc<OnceCell<Arc<Vec<u8>>>>,
+    remaining_parts: usize,
+}
+
+impl SharedChunks {
+    fn new<'a>(parts: impl Iterator<Item = &'a ChunkPart>) -> Self {
+        let mut slots = std::collections::HashMap::new();
+        for part in parts {
+            slots
+                .entry(part.guid)
+                .or_insert_with(|| SharedChunk {
+                    data: Arc::new(OnceCell::new()),
+                    remaining_parts: 0,
+                }).remaining_parts += 1;
+        }
+
+        Self { slots: Mutex::new(slots) }
+    }
+
+    /// The bytes `part` refers to, fetching its chunk only if no other part has.
+    async fn part(
+        &self,
+        source: &EpicChunkSource,
+        part: &ChunkPart,
+        build_id: &str,
+        download_manager: &DownloadManager
+    ) -> Result<Vec<u8>, LauncherError> {
+        let cell = self.slots
+            .lock().await
+            .get(&part.guid)
+            .map(|slot| slot.data.clone())
+            .ok_or_else(|| LauncherError::internal(format!("Unknown chunk {}", part.guid)))?;
+
+        // Parts asking at the same time wait for the first fetch; a failed fetch
+        // leaves the cell empty for the next one to try.
+        let chunk = cell
+            .get_or_try_init(|| async {
+                source.fetch(part.guid, build_id, download_manager).await.map(Arc::new)
+            }).await?
+            .clone();
+        let data = epic_manifest::part_data(&chunk, part)?.to_vec();
+
+        let mut slots = self.slots.lock().await;
+        if let Some(slot) = slots.get_mut(&part.guid) {
+            slot.remaining_parts -= 1;
+            if slot.remaining_parts == 0 {
+                slots.remove(&part.guid);
+            }
+        }
+
+        Ok(data)
+    }
+}
+
+/// Where the chunks of an Epic manifest are fetched from.
+struct EpicChunkSource {
+    manifest: EpicManifest,
+    base_url: String,
+    client: Client,
+    cache: Option<ChunkCache>,
+}
+
+impl EpicChunkSource {
+    /// Returns one chunk, unpacked. Network errors and damaged chunks are retried
+    /// `MAX_RETRIES` times.
+    async fn fetch(
+        &self,
+        guid: Guid,
+        build_id: &str,
+        download_manager: &DownloadManager
+    ) -> Result<Vec<u8>, LauncherError> {
+        let chunk = self.manifest.chunks
+            .get(&guid)
+            .ok_or_else(|| LauncherError::internal(format!("Unknown chunk {}", guid)))?;
+        let cache_key = format!("epic-{}", guid);
+
+        if let Some(cache) = &self.cache {
+            if let Some(cache_path) = cache.get(&cache_key).await {
+                if let Ok(data) = tokio::fs::read(&cache_path).await {
+                    if let Ok(decoded) = decode_epic_chunk(Arc::new(data), chunk).await {
+                        return Ok(decoded);
+                    }
+                }
+                cache.remove(&cache_key).await;
+            }
+        }
+
+        let chunk_url = format!("{}/{}", self.base_url, self.manifest.chunk_path(chunk));
+        let mut retries = 0;
+
+        loop {
+            let error = match self.client.get(&chunk_url).send().await {
+                Ok(response) if response.status().is_success() => {
+                    match read_body(response, build_id, download_manager).await {
+                        Ok(data) => {
+                            let data = Arc::new(data);
+                            match decode_epic_chunk(data.clone(), chunk).await {
+                                Ok(decoded) => {
+                                    self.cache_chunk(&cache_key, &data).await;
+                                    return Ok(decoded);
+                                }
+                                Err(e) => e,
+                            }
+                        }
+                        Err(e) => LauncherError::from(e).prefixed("Failed to download chunk data"),
+                    }
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let error = LauncherError::http_status(
+                        status,
+                        &chunk_url,
+                        format!("Failed to download chunk {}: HTTP {}", guid, status)
+                    );
+                    if !error.retryable {
+                        return Err(error);
+                    }
+                    error
+                }
+                Err(e) => {
+                    LauncherError::from(e).prefixed(
+                        format!("Network error downloading chunk {}", guid)
+                    )
+                }
+            };
+
+            retries += 1;
+            if retries >= MAX_RETRIES {
+                let mut error = error;
+                error.message = format!("{} (after {} attempts)", error.message, retries);
+                return Err(error);
+            }
+
+            tokio::time::sleep(Duration::from_millis(RETRY_DELAY_MS * (retries as u64))).await;
+        }
+    }
+
+    async fn cache_chunk(&self, cache_key: &str, data: &[u8]) {
+        let Some(cache) = &self.cache else {
+            return;
+        };
+        if let Some(mut writer) = cache.writer(cache_key).await {
+            if writer.write(data).await.is_ok() {
+                cache.commit(writer).await;
+            }
+        }
+    }
+}
+
+async fn decode_epic_chunk(
+    data: Arc<Vec<u8>>,
+    chunk: &ChunkInfo
+) -> Result<Vec<u8>, LauncherError> {
+    let chunk = chunk.clone();
+    let guid = chunk.guid;
+
+    tokio::task
+        ::spawn_blocking(move || epic_manifest::decode_chunk(&data, &chunk)).await
+        .context(format!("Failed to decode chunk {}", guid))?
+}
+
+/// Reads a whole response body, holding each piece back as long as the download's
+/// rate limit requires.
+async fn read_body(
+    response: reqwest::Response,
+    build_id: &str,
+    download_manager: &DownloadManager
+) -> Result<Vec<u8>, reqwest::Error> {
+    let mut body = Vec::with_capacity(response.content_length().unwrap_or(0) as usize);
+    let mut stream = response.bytes_stream();
+
+    while let Some(piece) = stream.next().await {
+        let piece = piece?;
+        download_manager.throttle(build_id, piece.len() as u64).await;
+        body.extend_from_slice(&piece);
+    }
+
+    Ok(body)
+}
+
+/// Writes `files` under `base_path` from their chunks, skipping whatever `journal`
+/// records as already on disk. Progress is reported against `journal.total_bytes`.
+pub(super) async fn install_chunked_files(
+    window: &Window,
+    build_id: &str,
+    files: &[&ChunkedFile],
+    base_path: &Path,
+    source: &ChunkSource,
+    journal: &mut InstallJournal,
+    download_manager: &DownloadManager
+) -> Result<(), LauncherError> {
+    // Anything left here belongs to an attempt that was cut short.
+    let staging_dir = base_path.join(STAGING_DIR);
+    let _ = tokio::fs::remove_dir_all(&staging_dir).await;
+    tokio::fs::create_dir_all(&staging_dir).await?;
+
+    let result = write_chunked_files(
+        window,
+        build_id,
+        files,
+        base_path,
+        source,
+        journal,
+        download_manager
+    ).await;
+
+    // Staged chunks are never resumed from, so they go however the install ended.
+    let _ = tokio::fs::remove_dir_all(&staging_dir).await;
+
+    result
+}
+
+async fn write_chunked_files(
+    window: &Window,
+    build_id: &str,
+    files: &[&ChunkedFile],
+    base_path: &Path,
+    source: &ChunkSource,
+    journal: &mut InstallJournal,
+    download_manager: &DownloadManager
+) -> Result<(), LauncherError> {
+    let staging_dir = base_path.join(STAGING_DIR);
+    let staging_dir = staging_dir.as_path();
+    let total_size = journal.total_bytes;
+    let mut last_update = std::time::Instant::now();
+    let mut last_journal_save = std::time::Instant::now();
+
+    // Work out how much of each file a previous attempt already left on disk, as
+    // (chunks already written, bytes already written).
+    let mut resume_points = vec![(0usize, 0u64); files.len()];
+    let mut completed_size: u64 = 0;
+    let mut resumed_file = None;
+
+    for (index, chunked_file) in files.iter().copied().enumerate() {
+        let file_path = base_path.join(&chunked_file.file);
+
+        if journal.completed_files.contains(&chunked_file.file) {
+            if file_path.exists() {
+                resume_points[index] = (chunked_file.chunks_ids.len(), chunked_file.file_size as u64);
+                completed_size += chunked_file.file_size as u64;
+                continue;
+            }
+            journal.completed_files.remove(&chunked_file.file);
+        }
+
+        if let Some(progress) = &journal.current_file {
+            if progress.file == chunked_file.file {
+                let part_len = tokio::fs
+                    ::metadata(part_path(&file_path)).await
+                    .map(|metadata| metadata.len())
+                    .unwrap_or(0);
+
+                if
+                    part_len >= progress.bytes_written &&
+                    progress.completed_chunks <= chunked_file.chunks_ids.len()
+                {
+                    resume_points[index] = (progress.completed_chunks, progress.bytes_written);
+                    completed_size += progress.bytes_written;
+                    resumed_file = Some(progress.clone());
+                }
+            }
+        }
+    }
+
+    journal.current_file = resumed_file;
+    journal.completed_bytes = completed_size;
+    let _ = journal.save().await;
+
+    // Chunks are fetched up to `chunk_workers` at a time, but `buffered` yields them
+    // in the order they were queued, so each file is still written front to back.
+    let chunk_jobs = files
+        .iter()
+        .copied()
+        .zip(&resume_points)
+        .flat_map(|(chunked_file, (skip, _))| {
+            chunked_file.chunks_ids
+                .iter()
+                .enumerate()
+                .skip(*skip)
+                .map(move |(index, chunk_id)| (chunked_file, index, *chunk_id))
+        });
+
+    let mut chunk_stream = stream
+        ::iter(chunk_jobs)
+        .map(|(chunked_file, index, chunk_id)| {
+            let expected_hash = chunked_file.chunks_hashes.get(index).map(String::as_str);
+            let compression = chunked_file.chunk_compression(index, source.compression);
+
+            async move {
+                // Paused downloads stop taking new chunks; ones already in flight still land.
+                if !download_manager.wait_while_paused(window, build_id).await {
+                    return Err(LauncherError::cancelled("Download cancelled"));
+                }
+
+                fetch_chunk(
+                    source,
+                    chunk_id,
+                    expected_hash,
+                    compression,
+                    staging_dir,
+                    build_id,
+                    download_manager
+                ).await.context(&chunked_file.file)
+            }
+        })
+        .buffered(source.workers);
+
+    for (chunked_file, &(skip, skipped_bytes)) in files.iter().copied().zip(&resume_points) {
+        if journal.completed_files.contains(&chunked_file.file) {
+            continue;
+        }
+
+        let file_path = base_path.join(&chunked_file.file);
+        if let Some(parent) = file_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let temp_file_path = part_path(&file_path);
+
+        let mut output_file = if skip > 0 {
+            let mut file = tokio::fs::OpenOptions::new().write(true).open(&temp_file_path).await?;
+            file.set_len(skipped_bytes).await?;
+            file.seek(SeekFrom::End(0)).await?;
+            file
+        } else {
+            AsyncFile::create(&temp_file_path).await?
+        };
+
+        journal.current_file = Some(FileProgress {
+            file: chunked_file.file.clone(),
+            completed_chunks: skip,
+            bytes_written: skipped_bytes,
+        });
+
+        let mut file_hasher = if chunked_file.file_hash.is_none() {
+            None
+        } else if skip > 0 {
+            let resumed_path = PathBuf::from(&temp_file_path);
+            Some(
+                tokio::task::spawn_blocking(move || {
+                    checksum::sha256_prefix(&resumed_path, skipped_bytes)
+                }).await??
+            )
+        } else {
+            Some(Sha256::new())
+        };
+
+        for _ in skip..chunked_file.chunks_ids.len() {
+            if !download_manager.is_active(build_id).await {
+                output_file.flush().await?;
+                return Err(LauncherError::cancelled("Download cancelled"));
+            }
+
+            if download_manager.is_paused(build_id).await {
+                output_file.flush().await?;
+                let _ = journal.save().await;
+                last_journal_save = std::time::Instant::now();
+
+                report_paused(window, build_id, completed_size, total_size, download_manager).await;
+            }
+
+            let staged = match chunk_stream.next().await {
+                Some(Ok(staged)) => staged,
+                Some(Err(e)) => {
+                    output_file.flush().await?;
+                    return Err(e);
+                }
+                None => {
+                    output_file.flush().await?;
+                    return Err(LauncherError::internal("Chunk stream ended unexpectedly"));
+                }
+            };
+
+            staged.append_to(&mut output_file, file_hasher.as_mut()).await?;
+
+            completed_size += staged.len();
+            journal.completed_bytes = completed_size;
+            if let Some(progress) = journal.current_file.as_mut() {
+                progress.completed_chunks += 1;
+                progress.bytes_written += staged.len();
+            }
+
+            if last_journal_save.elapsed().as_millis() > (JOURNAL_SAVE_INTERVAL_MS as u128) {
+                output_file.flush().await?;
+                let _ = journal.save().await;
+                last_journal_save = std::time::Instant::now();
+            }
+
+            report_progress(
+                window,
+                build_id,
+                completed_size,
+                total_size,
+                &mut last_update,
+                download_manager
+            ).await;
+        }
+
+        output_file.flush().await?;
+        drop(output_file);
+
+        if let (Some(expected_hash), Some(hasher)) = (&chunked_file.file_hash, file_hasher) {
+            let mut actual_hash = checksum::finalize_hex(hasher);
+
+            if !checksum::hashes_match(expected_hash, &actual_hash) {
+                actual_hash = refetch_file(
+                    source,
+                    chunked_file,
+                    &temp_file_path,
+                    staging_dir,
+                    build_id,
+                    download_manager
+                ).await?;
+            }
+
+            if !checksum::hashes_match(expected_hash, &actual_hash) {
+                let _ = tokio::fs::remove_file(&temp_file_path).await;
+                journal.current_file = None;
+                return Err(
+                    LauncherError::corrupt_data(
+                        format!(
+                            "{} failed hash verification: expected {}, got {}",
+                            chunked_file.file,
+                            expected_hash,
+                            actual_hash
+                        )
+                    )
+                        .with_context("file", chunked_file.file.as_str())
+                        .with_context("expected_hash", expected_hash.as_str())
+                        .with_context("actual_hash", actual_hash)
+                );
+            }
+        }
+
+        if file_path.exists() {
+            tokio::fs::remove_file(&file_path).await?;
+        }
+
+        tokio::fs::rename(&temp_file_path, &file_path).await?;
+
+        journal.current_file = None;
+        journal.completed_files.insert(chunked_file.file.clone());
+    }
+
+    Ok(())
+}
+
+/// Feeds `completed_size` into the speed estimate and sends a progress event once the
+/// last one is more than `UPDATE_INTERVAL_MS` old.
+async fn report_progress(
+    window: &Window,
+    build_id: &str,
+    completed_size: u64,
+    total_size: u64,
+    last_update: &mut Instant,
+    download_manager: &DownloadManager
+) {
+    let percentage = ((completed_size as f64) / (total_size as f64)) * 100.0;
+
+    let speed = download_manager.update_speed_data(build_id, completed_size).await;
+    let remaining_bytes = total_size.saturating_sub(completed_size);
+    let eta_seconds = if speed > 0.0 {
+        (remaining_bytes as f64) / speed
+    } else {
+        f64::INFINITY
+    };
+    let eta = format_time(eta_seconds);
+
+    if last_update.elapsed().as_millis() > (UPDATE_INTERVAL_MS as u128) {
+        download_manager.emit_progress(window, DownloadProgress {
+            build_id: build_id.to_string(),
+            percentage,
+            downloaded_bytes: completed_size,
+            total_bytes: total_size,
+            speed,
+            eta,
+            paused: false,
+            rate_limit: download_manager.rate_limit(build_id).await,
+        }).await;
+
+        *last_update = Instant::now();
+    }
+}
+
+async fn report_paused(
+    window: &Window,
+    build_id: &str,
+    completed_size: u64,
+    total_size: u64,
+    download_manager: &DownloadManager
+) {
+    download_manager.emit_progress(window, DownloadProgress {
+        build_id: build_id.to_string(),
+        percentage: ((completed_size as f64) / (total_size as f64)) * 100.0,
+        downloaded_bytes: completed_size,
+        total_bytes: total_size,
+        speed: 0.0,
+        eta: "Paused".to_string(),
+        paused: true,
+        rate_limit: download_manager.rate_limit(build_id).await,
+    }).await;
+}
+
+/// Where the chunks of one manifest version are fetched from, and how many at once.
+/// Every request goes to the current mirror until it racks up `MIRROR_STRIKES`
+/// failed or slow responses in a row, after which all of them move to the next one.
+pub(super) struct ChunkSource {
+    client: Client,
+    release: String,
+    /// What chunks are compressed with unless their file says otherwise.
+    compression: Compression,
+    cache: Option<ChunkCache>,
+    mirrors: Vec<String>,
+    current_mirror: AtomicUsize,
+    strikes: AtomicUsize,
+    workers: usize,
+}
+
+impl ChunkSource {
+    pub(super) async fn new(
+        version: &str,
+        compression: Compression,
+        workers: usize
+    ) -> Result<Self, LauncherError> {
+        let client = network
+            ::client_builder().await?
+            .pool_max_idle_per_host(workers.max(20))
+            .pool_idle_timeout(std::time::Duration::from_secs(30))
+            .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .connect_timeout(std::time::Duration::from_secs(10))
+            .build()?;
+
+        let extracted_version = BuildVersion::parse(version)?.manifest_name();
+
+        let mirrors = DownloadSettings::load().await.manifest_mirrors
+            .iter()
+            .map(|mirror| format!("{}/{}", mirror, extracted_version))
+            .collect();
+
+        Ok(Self {
+            client,
+            release: extracted_version,
+            compression,
+            cache: ChunkCache::open().await,
+            mirrors,
+            current_mirror: AtomicUsize::new(0),
+            strikes: AtomicUsize::new(0),
+            workers,
+        })
+    }
+
+    fn current_mirror(&self) -> usize {
+        self.current_mirror.load(Ordering::Relaxed)
+    }
+
+    fn chunk_url(&self, mirror: usize, chunk_id: i32) -> String {
+        format!("{}/{}.chunk", self.mirrors[mirror], chunk_id)
+    }
+
+    /// Counts a failed or slow response against `mirror`. Strikes against a mirror
+    /// that has already been left behind are ignored.
+    fn strike(&self, mirror: usize) {
+        if self.current_mirror() != mirror {
+            return;
         }
-    };
+        if self.strikes.fetch_add(1, Ordering::Relaxed) + 1 >= MIRROR_STRIKES {
+            self.abandon(mirror);
+        }
+    }
 
-    let base_path = Path::new(install_path);
-    if let Some(parent) = base_path.parent() {
-        tokio::fs::create_dir_all(parent).await?;
+    fn clear_strikes(&self, mirror: usize) {
+        if self.current_mirror() == mirror {
+            self.strikes.store(0, Ordering::Relaxed);
+        }
     }
 
-    for chunked_file in manifest.chunks {
-        let file_path = base_path.join(&chunked_file.file);
-        if let Some(parent) = file_path.parent() {
-            tokio::fs::create_dir_all(parent).await?;
+    /// Moves every later request on from `mirror`, wrapping back to the first mirror
+    /// after the last.
+    fn abandon(&self, mirror: usize) {
+        let next = (mirror + 1) % self.mirrors.len();
+        if
+            self.current_mirror
+                .compare_exchange(mirror, next, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        {
+            self.strikes.store(0, Ordering::Relaxed);
+            if next != mirror {
+                log::info!("Switching chunk downloads to mirror {}", self.mirrors[next]);
+            }
         }
+    }
+}
 
-        let temp_file_path = format!("{}.part", file_path.to_string_lossy());
+/// Refuses to start writing when the volume holding `path` can't fit the download.
+async fn ensure_disk_space(
+    path: &Path,
+    required_bytes: u64,
+    overhead_bytes: u64
+) -> Result<(), LauncherError> {
+    let path = path.to_path_buf();
+    let report = tokio::task::spawn_blocking(move || {
+        DiskSpaceReport::new(&path, required_bytes, overhead_bytes)
+    }).await?;
+
+    report.ensure_sufficient()?;
+    Ok(())
+}
 
-        let mut output_file = AsyncFile::create(&temp_file_path).await?;
+fn part_path(file_path: &Path) -> String {
+    format!("{}.part", file_path.to_string_lossy())
+}
 
-        for chunk_id in &chunked_file.chunks_ids {
-            {
-                let active_downloads = download_manager.active_downloads.lock().await;
-                if !active_downloads.contains(&build_id) {
-                    let _ = tokio::fs::remove_file(&temp_file_path).await;
-                    return Err("Download cancelled".into());
+/// Downloads a single chunk with retries and stages its decompressed contents under
+/// `staging_dir`. The body is decompressed as it streams in, so memory use stays
+/// fixed whatever the chunk's size. A chunk that fails to decompress or doesn't match
+/// its manifest hash is fetched again, the same as a network error. Each mirror gets
+/// `MAX_RETRIES` attempts. The shared chunk cache is checked first, and every verified
+/// download is added to it.
+async fn fetch_chunk(
+    source: &ChunkSource,
+    chunk_id: i32,
+    expected_hash: Option<&str>,
+    compression: Compression,
+    staging_dir: &Path,
+    build_id: &str,
+    download_manager: &DownloadManager
+) -> Result<StagedChunk, LauncherError> {
+    let cache_key = format!(
+        "{}{}",
+        ChunkCache::key(&source.release, chunk_id, expected_hash),
+        compression.cache_suffix()
+    );
+
+    if let Some(cache) = &source.cache {
+        if let Some(cache_path) = cache.get(&cache_key).await {
+            let staging = decompress::staging_path(staging_dir, chunk_id);
+            let expected_hash = expected_hash.map(str::to_string);
+            let decoded = tokio::task::spawn_blocking(move || {
+                let compressed = File::open(&cache_path).context("Failed to open cached chunk")?;
+                decompress::decode_chunk(
+                    compressed,
+                    compression,
+                    staging,
+                    chunk_id,
+                    expected_hash.as_deref()
+                )
+            }).await;
+
+            match decoded {
+                Ok(Ok(staged)) => {
+                    return Ok(staged);
                 }
+                _ => cache.remove(&cache_key).await,
             }
+        }
+    }
 
-            let chunk_url = format!("{}/{}/{}.chunk", BASE_URL, extracted_version, chunk_id);
+    let max_attempts = MAX_RETRIES * source.mirrors.len();
+    let mut retries = 0;
 
-            let mut retries = 0;
-            let mut chunk_data = None;
+    loop {
+        let mirror = source.current_mirror();
+        let chunk_url = source.chunk_url(mirror, chunk_id);
+        let request_start = Instant::now();
+
+        let error = match source.client.get(&chunk_url).send().await {
+            Ok(response) => {
+                let slow = request_start.elapsed() > Duration::from_millis(SLOW_MIRROR_MS);
+
+                if response.status().is_success() {
+                    let staged = stream_chunk(
+                        response,
+                        source,
+                        &cache_key,
+                        compression,
+                        decompress::staging_path(staging_dir, chunk_id),
+                        chunk_id,
+                        expected_hash,
+                        build_id,
+                        download_manager
+                    ).await;
 
-            while retries < MAX_RETRIES && chunk_data.is_none() {
-                match client.get(&chunk_url).send().await {
-                    Ok(response) => {
-                        if response.status().is_success() {
-                            match response.bytes().await {
-                                Ok(data) => {
-                                    chunk_data = Some(data);
-                                }
-                                Err(e) => {
-                                    retries += 1;
-                                    if retries >= MAX_RETRIES {
-                                        let _ = tokio::fs::remove_file(&temp_file_path).await;
-                                        return Err(
-                                            format!("Failed to download chunk data: {}", e).into()
-                                        );
-                                    }
-                                    tokio::time::sleep(
-                                        Duration::from_millis(RETRY_DELAY_MS * (retries as u64))
-                                    ).await;
-                                }
-                            }
-                        } else if response.status().is_server_error() {
-                            retries += 1;
-                            if retries >= MAX_RETRIES {
-                                let _ = tokio::fs::remove_file(&temp_file_path).await;
-                                return Err(
-                                    format!(
-                                        "Failed to download chunk {}: HTTP {}",
-                                        chunk_id,
-                                        response.status()
-                                    ).into()
-                                );
+                    match staged {
+                        Ok(staged) => {
+                            if slow {
+                                source.strike(mirror);
+                            } else {
+                                source.clear_strikes(mirror);
                             }
-                            tokio::time::sleep(
-                                Duration::from_millis(RETRY_DELAY_MS * (retries as u64))
-                            ).await;
-                        } else {
-                            // Client error, don't retry
-                            // Clean up temp file
-                            let _ = tokio::fs::remove_file(&temp_file_path).await;
-                            return Err(
-                                format!(
-                                    "Failed to download chunk {}: HTTP {}",
-                                    chunk_id,
-                                    response.status()
-                                ).into()
-                            );
+                            return Ok(staged);
                         }
+                        Err(e) => e,
                     }
-                    Err(e) => {
-                        retries += 1;
-                        if retries >= MAX_RETRIES {
-                            let _ = tokio::fs::remove_file(&temp_file_path).await;
-                            return Err(
-                                format!(
-                                    "Network error downloading chunk {}: {}",
-                                    chunk_id,
-                                    e
-                                ).into()
-                            );
-                        }
-                        tokio::time::sleep(
-                            Duration::from_millis(RETRY_DELAY_MS * (retries as u64))
-                        ).await;
+                } else {
+                    let status = response.status();
+                    let error = LauncherError::http_status(
+                        status,
+                        &chunk_url,
+                        format!("Failed to download chunk {}: HTTP {}", chunk_id, status)
+                    );
+
+                    if status.is_server_error() {
+                        error
+                    } else if source.mirrors.len() > 1 {
+                        // This mirror doesn't have the chunk, so don't wait for more strikes.
+                        source.abandon(mirror);
+                        error
+                    } else {
+                        // Client error, don't retry
+                        return Err(error);
                     }
                 }
             }
+            Err(e) => {
+                LauncherError::from(e).prefixed(
+                    format!("Network error downloading chunk {}", chunk_id)
+                )
+            }
+        };
+
+        source.strike(mirror);
+        retries += 1;
+        if retries >= max_attempts {
+            let mut error = error;
+            error.message = format!("{} (after {} attempts)", error.message, retries);
+            return Err(error);
+        }
 
-            let chunk_data = chunk_data.ok_or("Failed to download chunk")?;
+        let backoff = (retries % MAX_RETRIES).max(1) as u64;
+        tokio::time::sleep(Duration::from_millis(RETRY_DELAY_MS * backoff)).await;
+    }
+}
 
-            let mut decoder = GzDecoder::new(&chunk_data[..]);
-            let mut decompressed_data = Vec::new();
-            decoder.read_to_end(&mut decompressed_data)?;
+/// Feeds a chunk response to the decoder on a blocking thread as it arrives, holding
+/// each piece back as long as the download's rate limit requires. The compressed
+/// bytes are copied into the chunk cache on the way and kept once the chunk verifies.
+async fn stream_chunk(
+    response: reqwest::Response,
+    source: &ChunkSource,
+    cache_key: &str,
+    compression: Compression,
+    staging: PathBuf,
+    chunk_id: i32,
+    expected_hash: Option<&str>,
+    build_id: &str,
+    download_manager: &DownloadManager
+) -> Result<StagedChunk, LauncherError> {
+    let (sender, reader) = decompress::piece_channel();
+    let expected_hash = expected_hash.map(str::to_string);
+    let decoder = tokio::task::spawn_blocking(move || {
+        decompress::decode_chunk(reader, compression, staging, chunk_id, expected_hash.as_deref())
+    });
 
-            output_file.write_all(&decompressed_data).await?;
+    let mut cache_writer = match &source.cache {
+        Some(cache) => cache.writer(cache_key).await,
+        None => None,
+    };
 
-            completed_size += decompressed_data.len() as i64;
-            let percentage = ((completed_size as f64) / (total_size as f64)) * 100.0;
+    let mut stream = response.bytes_stream();
+    let mut network_error = None;
 
-            let speed = download_manager.update_speed_data(&build_id, completed_size as u64).await;
-            let remaining_bytes = total_size - completed_size;
-            let eta_seconds = if speed > 0.0 {
-                (remaining_bytes as f64) / speed
-            } else {
-                f64::INFINITY
-            };
-            let eta = format_time(eta_seconds);
+    while let Some(piece) = stream.next().await {
+        let piece = match piece {
+            Ok(piece) => piece,
+            Err(e) => {
+                network_error = Some(e);
+                break;
+            }
+        };
 
-            if last_update.elapsed().as_millis() > (UPDATE_INTERVAL_MS as u128) {
-                let _ = window.emit("download:progress", DownloadProgress {
-                    build_id: build_id.clone(),
-                    percentage,
-                    downloaded_bytes: completed_size as u64,
-                    total_bytes: total_size as u64,
-                    speed,
-                    eta,
-                });
+        download_manager.throttle(build_id, piece.len() as u64).await;
 
-                last_update = std::time::Instant::now();
+        if let Some(writer) = cache_writer.as_mut() {
+            if writer.write(&piece).await.is_err() {
+                cache_writer = None;
             }
         }
 
-        output_file.flush().await?;
-        drop(output_file);
-
-        if file_path.exists() {
-            tokio::fs::remove_file(&file_path).await?;
+        // The decoder only hangs up early when the chunk is already known to be bad.
+        if sender.send(piece).await.is_err() {
+            break;
         }
+    }
+    drop(sender);
 
-        tokio::fs::rename(&temp_file_path, &file_path).await?;
+    let decoded = decoder.await;
+    if let Some(e) = network_error {
+        return Err(LauncherError::from(e).prefixed("Failed to download chunk data"));
     }
+    let staged = decoded.context(format!("Failed to decode chunk {}", chunk_id))??;
 
-    let _ = window.emit("download:progress", DownloadProgress {
-        build_id: build_id.clone(),
-        percentage: 100.0,
-        downloaded_bytes: total_size as u64,
-        total_bytes: total_size as u64,
-        speed: 0.0,
-        eta: "0s".to_string(),
-    });
+    if let (Some(cache), Some(writer)) = (&source.cache, cache_writer) {
+        cache.commit(writer).await;
+    }
 
-    let _ = window.emit("download:completed", build_id);
+    Ok(staged)
+}
 
-    Ok(())
+/// Rebuilds one file from scratch by fetching its chunks one at a time. Used when a
+/// finished file fails its hash even though every chunk arrived intact.
+async fn refetch_file(
+    source: &ChunkSource,
+    chunked_file: &ChunkedFile,
+    temp_file_path: &str,
+    staging_dir: &Path,
+    build_id: &str,
+    download_manager: &DownloadManager
+) -> Result<String, LauncherError> {
+    let mut output_file = AsyncFile::create(temp_file_path).await?;
+    let mut hasher = Sha256::new();
+
+    for (index, chunk_id) in chunked_file.chunks_ids.iter().enumerate() {
+        let expected_hash = chunked_file.chunks_hashes.get(index).map(String::as_str);
+        let staged = fetch_chunk(
+            source,
+            *chunk_id,
+            expected_hash,
+            chunked_file.chunk_compression(index, source.compression),
+            staging_dir,
+            build_id,
+            download_manager
+        ).await?;
+
+        staged.append_to(&mut output_file, Some(&mut hasher)).await?;
+    }
+
+    output_file.flush().await?;
+
+    Ok(checksum::finalize_hex(hasher))
 }
 
+/// Downloads `url` to `destination`. Large files from servers that accept byte ranges
+/// are fetched over `connections` connections at once, everything else as one stream.
 async fn download_file(
     window: Window,
     build_id: String,
     url: &str,
     destination: &str,
+    extract: bool,
+    connections: usize,
     download_manager: &State<'_, DownloadManager>
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let client = Client::builder()
+) -> Result<(), LauncherError> {
+    let client = network
+        ::client_builder().await?
         .pool_max_idle_per_host(20)
         .pool_idle_timeout(std::time::Duration::from_secs(30))
         .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS))
         .connect_timeout(std::time::Duration::from_secs(10))
         .build()?;
 
-    let mut response = None;
-    let mut retries = 0;
-    let mut last_error = String::new();
-
-    while retries < MAX_RETRIES && response.is_none() {
-        match client.get(url).send().await {
-            Ok(res) => {
-                if res.status().is_success() {
-                    response = Some(res);
-                } else if res.status().is_server_error() {
-                    last_error = format!("Server error: HTTP {}", res.status());
-                    retries += 1;
-                    tokio::time::sleep(
-                        Duration::from_millis(RETRY_DELAY_MS * (retries as u64))
-                    ).await;
-                } else {
-                    return Err(format!("Failed to download file: HTTP {}", res.status()).into());
+    if connections > 1 {
+        if let Some(fresh) = probe_ranges(&client, url).await {
+            if fresh.total_size >= SEGMENT_MIN_BYTES {
+                let partial = plan_segments(destination, fresh, connections, extract).await?;
+                let segments = SegmentedDownload {
+                    window: &window,
+                    build_id: &build_id,
+                    client: &client,
+                    url,
+                    destination,
+                    total_size: partial.total_size,
+                    downloaded: AtomicU64::new(partial.total_size - partial.remaining_bytes()),
+                    last_update: Mutex::new(Instant::now()),
+                    saved_at: Mutex::new(Instant::now()),
+                    partial: Mutex::new(partial),
+                    download_manager,
+                };
+
+                match segments.run().await {
+                    Err(e) if e.code == ErrorCode::Unsupported => {
+                        log::info!("{}, downloading as a single stream instead", e.message);
+                    }
+                    result => {
+                        return result;
+                    }
                 }
             }
-            Err(e) => {
-                last_error = format!("Network error: {}", e);
-                retries += 1;
-                if retries >= MAX_RETRIES {
+        }
+    }
+
+    download_single_stream(
+        window,
+        build_id,
+        &client,
+        url,
+        destination,
+        extract,
+        download_manager
+    ).await
+}
+
+/// Size and validators of the file at `url`, if its server says it serves byte ranges.
+async fn probe_ranges(client: &Client, url: &str) -> Option<PartialDownload> {
+    let response = client.head(url).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+
+    let header = |name| response.headers().get(name).and_then(|value| value.to_str().ok());
+    if !header(ACCEPT_RANGES).is_some_and(|value| value.eq_ignore_ascii_case("bytes")) {
+        return None;
+    }
+
+    // `content_length` is always zero for a HEAD response, so read the header itself.
+    let total_size = header(CONTENT_LENGTH)
+        .and_then(|value| value.parse().ok())
+        .filter(|&len| len > 0)?;
+
+    Some(PartialDownload::new(url, total_size, response.headers()))
+}
+
+/// Picks up the segments a previous attempt saved for `destination` when they belong
+/// to the same file as `fresh`. Otherwise splits `fresh` into `connections` new
+/// segments over an empty file of the full size.
+async fn plan_segments(
+    destination: &str,
+    mut fresh: PartialDownload,
+    connections: usize,
+    extract: bool
+) -> Result<PartialDownload, LauncherError> {
+    if let Some(saved) = PartialDownload::load(destination, &fresh.url).await {
+        let file_len = tokio::fs
+            ::metadata(destination).await
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+
+        let resumable = !saved.segments.is_empty() && saved.same_resource(&fresh);
+        if resumable && file_len == saved.total_size {
+            return Ok(saved);
+        }
+    }
+
+    let (required_bytes, overhead_bytes) = disk_space::direct_requirement(
+        fresh.total_size,
+        extract
+    );
+    ensure_disk_space(Path::new(destination), required_bytes, overhead_bytes).await?;
+
+    let total_size = fresh.total_size;
+    let segment_size = total_size.div_ceil(connections as u64);
+    fresh.segments = (0..connections as u64)
+        .map(|index| (index * segment_size, ((index + 1) * segment_size).min(total_size)))
+        .filter(|(start, end)| start < end)
+        .collect();
+
+    AsyncFile::create(destination).await?.set_len(total_size).await?;
+    let _ = fresh.save(destination).await;
+
+    Ok(fresh)
+}
+
+/// A direct download split into byte ranges, each fetched on its own connection and
+/// written straight to its place in the file. How far each range got is saved beside
+/// the file, so an interrupted download carries on from there.
+struct SegmentedDownload<'a> {
+    window: &'a Window,
+    build_id: &'a str,
+    client: &'a Client,
+    url: &'a str,
+    destination: &'a str,
+    total_size: u64,
+    downloaded: AtomicU64,
+    last_update: Mutex<Instant>,
+    saved_at: Mutex<Instant>,
+    partial: Mutex<PartialDownload>,
+    download_manager: &'a DownloadManager,
+}
+
+impl SegmentedDownload<'_> {
+    /// Fails with an `Unsupported` error when the server turns out to ignore ranges,
+    /// or the file changed since the saved segments were written, after clearing the
+    /// file for a fresh single-stream download.
+    async fn run(&self) -> Result<(), LauncherError> {
+        let segment_count = self.partial.lock().await.segments.len();
+        let segments = (0..segment_count).map(|index| self.fetch(index));
+
+        if let Err(e) = futures_util::future::try_join_all(segments).await {
+            if e.code == ErrorCode::Unsupported {
+                let _ = tokio::fs::remove_file(self.destination).await;
+                PartialDownload::remove(self.destination).await;
+            } else {
+                let _ = self.partial.lock().await.save(self.destination).await;
+            }
+            return Err(e);
+        }
+
+        let file_size = tokio::fs::metadata(self.destination).await?.len();
+        if file_size != self.total_size {
+            let _ = tokio::fs::remove_file(self.destination).await;
+            PartialDownload::remove(self.destination).await;
+            return Err(
+                LauncherError::corrupt_data(
+                    format!(
+                        "Downloaded file size ({}) doesn't match expected size ({})",
+                        file_size,
+                        self.total_size
+                    )
+                )
+            );
+        }
+
+        PartialDownload::remove(self.destination).await;
+
+        Ok(())
+    }
+
+    /// Downloads what is left of segment `index`. A dropped connection or a pause
+    /// picks up again from the last byte written; only `MAX_RETRIES` failures in a row
+    /// without any progress give up.
+    async fn fetch(&self, index: usize) -> Result<(), LauncherError> {
+        let download_manager = self.download_manager;
+        let (mut position, end) = self.partial.lock().await.segments[index];
+        let if_range = self.partial.lock().await.if_range().map(str::to_string);
+
+        let mut file = tokio::fs::OpenOptions::new().write(true).open(self.destination).await?;
+        let mut checkpoint = position;
+        let mut failures = 0;
+
+        'request: while position < end {
+            let mut request = self.client
+                .get(self.url)
+                .header(RANGE, format!("bytes={}-{}", position, end - 1));
+            if let Some(if_range) = &if_range {
+                request = request.header(IF_RANGE, if_range);
+            }
+
+            let error = match request.send().await {
+                Ok(response) if response.status() == StatusCode::PARTIAL_CONTENT => {
+                    file.seek(SeekFrom::Start(position)).await?;
+                    let mut stream = response.bytes_stream();
+
+                    loop {
+                        if !download_manager.is_active(self.build_id).await {
+                            return Err(LauncherError::cancelled("Download cancelled"));
+                        }
+
+                        if download_manager.is_paused(self.build_id).await {
+                            file.flush().await?;
+                            self.checkpoint(index, position, true).await;
+                            report_paused(
+                                self.window,
+                                self.build_id,
+                                self.downloaded.load(Ordering::Relaxed),
+                                self.total_size,
+                                download_manager
+                            ).await;
+
+                            let resumed = download_manager.wait_while_paused(
+                                self.window,
+                                self.build_id
+                            ).await;
+                            if !resumed {
+                                return Err(LauncherError::cancelled("Download cancelled"));
+                            }
+                            continue 'request;
+                        }
+
+                        let piece = match timeout(STALL_TIMEOUT, stream.next()).await {
+                            Ok(Some(Ok(piece))) => piece,
+                            Ok(Some(Err(e))) => {
+                                break LauncherError::from(e).prefixed("Error downloading file");
+                            }
+                            Ok(None) => {
+                                break LauncherError::new(
+                                    ErrorCode::Network,
+                                    "Connection closed before the segment finished"
+                                );
+                            }
+                            Err(_) => {
+                                break stall_error();
+                            }
+                        };
+
+                        // Never write past the segment, whatever the server sends.
+                        let len = piece.len().min((end - position) as usize);
+                        download_manager.throttle(self.build_id, len as u64).await;
+                        file.write_all(&piece[..len]).await?;
+                        position += len as u64;
+                        failures = 0;
+
+                        let downloaded =
+                            self.downloaded.fetch_add(len as u64, Ordering::Relaxed) +
+                            (len as u64);
+                        self.report(downloaded).await;
+
+                        if position >= end || position - checkpoint >= CHECKPOINT_BYTES {
+                            file.flush().await?;
+                            self.checkpoint(index, position, position >= end).await;
+                            checkpoint = position;
+                        }
+                        if position >= end {
+                            return Ok(());
+                        }
+                    }
+                }
+                Ok(response) if response.status().is_success() => {
                     return Err(
-                        format!(
-                            "Failed to download after {} attempts. Last error: {}",
-                            MAX_RETRIES,
-                            last_error
-                        ).into()
+                        LauncherError::unsupported(
+                            "The server sent the whole file instead of the requested range"
+                        )
+                    );
+                }
+                Ok(response) => {
+                    let error = LauncherError::http_status(
+                        response.status(),
+                        self.url,
+                        format!("Failed to download file: HTTP {}", response.status())
                     );
+                    if !error.retryable {
+                        return Err(error);
+                    }
+                    error
                 }
-                tokio::time::sleep(Duration::from_millis(RETRY_DELAY_MS * (retries as u64))).await;
+                Err(e) => LauncherError::from(e).prefixed("Network error"),
+            };
+
+            file.flush().await?;
+            self.checkpoint(index, position, true).await;
+            checkpoint = position;
+
+            failures += 1;
+            if failures >= MAX_RETRIES {
+                return Err(
+                    error.prefixed(
+                        format!("Failed to download after {} attempts. Last error", MAX_RETRIES)
+                    )
+                );
             }
+            tokio::time::sleep(Duration::from_millis(RETRY_DELAY_MS * (failures as u64))).await;
+        }
+
+        Ok(())
+    }
+
+    /// Records that segment `index` is safely written up to `position`, saving to disk
+    /// when `force` is set or the last save is older than `JOURNAL_SAVE_INTERVAL_MS`.
+    async fn checkpoint(&self, index: usize, position: u64, force: bool) {
+        let mut partial = self.partial.lock().await;
+        partial.segments[index].0 = position;
+
+        let mut saved_at = self.saved_at.lock().await;
+        if force || saved_at.elapsed().as_millis() > (JOURNAL_SAVE_INTERVAL_MS as u128) {
+            let _ = partial.save(self.destination).await;
+            *saved_at = Instant::now();
         }
     }
 
-    let res = response.ok_or(format!("Failed to download after {} attempts", MAX_RETRIES))?;
+    async fn report(&self, downloaded: u64) {
+        let mut last_update = self.last_update.lock().await;
+        report_progress(
+            self.window,
+            self.build_id,
+            downloaded,
+            self.total_size,
+            &mut last_update,
+            self.download_manager
+        ).await;
+    }
+}
 
-    let total_size = res.content_length().unwrap_or(0);
-    let has_content_length = total_size > 0;
+fn stall_error() -> LauncherError {
+    LauncherError::new(
+        ErrorCode::Timeout,
+        format!("Download stalled - no data received for {} seconds", STALL_TIMEOUT.as_secs())
+    )
+}
 
-    let mut file = tokio::fs::File::create(destination).await?;
+/// Downloads `url` over a single connection into `destination`, carrying on from
+/// whatever an earlier attempt left there when the saved validators say the file
+/// hasn't changed. Pausing, dropped connections and stalls all ask for the rest of
+/// the file with a Range header; the partial file is only deleted on cancel.
+async fn download_single_stream(
+    window: Window,
+    build_id: String,
+    client: &Client,
+    url: &str,
+    destination: &str,
+    extract: bool,
+    download_manager: &DownloadManager
+) -> Result<(), LauncherError> {
+    // Segmented progress can't be read off the file's length, so it isn't reused here.
+    let mut partial = PartialDownload::load(destination, url).await.filter(|partial| {
+        partial.segments.is_empty()
+    });
 
-    let mut stream = res.bytes_stream();
-    let mut downloaded_bytes = 0u64;
-    let start_time = std::time::Instant::now();
+    let mut downloaded_bytes = match &partial {
+        Some(_) => {
+            tokio::fs
+                ::metadata(destination).await
+                .map(|metadata| metadata.len())
+                .unwrap_or(0)
+        }
+        None => 0,
+    };
+    if partial.as_ref().is_some_and(|partial| downloaded_bytes > partial.total_size) {
+        downloaded_bytes = 0;
+    }
+    let mut complete = partial
+        .as_ref()
+        .is_some_and(|partial| partial.total_size > 0 && downloaded_bytes == partial.total_size);
+
+    let mut file = tokio::fs::OpenOptions
+        ::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(destination).await?;
+    file.set_len(downloaded_bytes).await?;
+    file.seek(SeekFrom::End(0)).await?;
+
+    let mut total_size = if complete { downloaded_bytes } else { 0 };
     let mut last_update = std::time::Instant::now();
-    let mut last_download_time = std::time::Instant::now();
-    let timeout_duration = Duration::from_secs(30); // Timeout if no data received for 30 seconds
+    let mut failures = 0;
+
+    // Each pass of this loop is one HTTP request. Pausing drops the connection and
+    // the next pass asks for the rest of the file with a Range header.
+    'request: while !complete {
+        let resumed_from = downloaded_bytes;
+        let res = request_rest(
+            client,
+            url,
+            destination,
+            &mut file,
+            &mut downloaded_bytes,
+            &mut partial
+        ).await?;
+        if downloaded_bytes != resumed_from {
+            total_size = 0;
+        }
 
-    while let Some(chunk_result) = stream.next().await {
-        {
-            let active_downloads = download_manager.active_downloads.lock().await;
-            if !active_downloads.contains(&build_id) {
-                file.flush().await?;
-                drop(file);
-                let _ = tokio::fs::remove_file(destination).await;
-                return Err("Download cancelled".into());
+        if total_size == 0 {
+            total_size = res.content_length().unwrap_or(0) + downloaded_bytes;
+
+            if total_size > 0 {
+                let (required_bytes, overhead_bytes) = disk_space::direct_requirement(
+                    total_size - downloaded_bytes,
+                    extract
+                );
+                ensure_disk_space(Path::new(destination), required_bytes, overhead_bytes).await?;
             }
         }
+        let has_content_length = total_size > 0;
 
-        if last_download_time.elapsed() > timeout_duration {
-            file.flush().await?;
-            drop(file);
-            let _ = tokio::fs::remove_file(destination).await;
-            return Err("Download timed out - no data received for 30 seconds".into());
+        if res.status() != StatusCode::PARTIAL_CONTENT || partial.is_none() {
+            let fresh = PartialDownload::new(url, total_size, res.headers());
+            let _ = fresh.save(destination).await;
+            partial = Some(fresh);
         }
 
-        match chunk_result {
-            Ok(chunk) => {
-                file.write_all(&chunk).await?;
-                downloaded_bytes += chunk.len() as u64;
-                last_download_time = std::time::Instant::now();
+        let mut stream = res.bytes_stream();
 
-                if last_update.elapsed().as_millis() > (UPDATE_INTERVAL_MS as u128) {
-                    let speed = download_manager.update_speed_data(
-                        &build_id,
-                        downloaded_bytes
-                    ).await;
-
-                    let (percentage, eta) = if has_content_length && total_size > 0 {
-                        let percentage: f64 =
-                            ((downloaded_bytes as f64) / (total_size as f64)) * 100.0;
-                        let remaining_bytes = total_size.saturating_sub(downloaded_bytes);
-                        let eta_seconds = if speed > 0.0 {
-                            (remaining_bytes as f64) / speed
-                        } else {
-                            f64::INFINITY
-                        };
-                        (percentage, format_time(eta_seconds))
-                    } else {
-                        (0.0, "Unknown".to_string())
-                    };
+        let error = loop {
+            if !download_manager.is_active(&build_id).await {
+                drop(file);
+                let _ = tokio::fs::remove_file(destination).await;
+                PartialDownload::remove(destination).await;
+                return Err(LauncherError::cancelled("Download cancelled"));
+            }
 
-                    let _ = window.emit("download:progress", DownloadProgress {
-                        build_id: build_id.clone(),
-                        percentage,
-                        downloaded_bytes,
-                        total_bytes: total_size,
-                        speed,
-                        eta,
-                    });
+            if download_manager.is_paused(&build_id).await {
+                file.flush().await?;
 
-                    last_update = std::time::Instant::now();
+                let percentage = if has_content_length {
+                    ((downloaded_bytes as f64) / (total_size as f64)) * 100.0
+                } else {
+                    0.0
+                };
+                download_manager.emit_progress(&window, DownloadProgress {
+                    build_id: build_id.clone(),
+                    percentage,
+                    downloaded_bytes,
+                    total_bytes: total_size,
+                    speed: 0.0,
+                    eta: "Paused".to_string(),
+                    paused: true,
+                    rate_limit: download_manager.rate_limit(&build_id).await,
+                }).await;
+
+                if !download_manager.wait_while_paused(&window, &build_id).await {
+                    drop(file);
+                    let _ = tokio::fs::remove_file(destination).await;
+                    PartialDownload::remove(destination).await;
+                    return Err(LauncherError::cancelled("Download cancelled"));
                 }
+
+                continue 'request;
             }
-            Err(e) => {
-                file.flush().await?;
-                drop(file);
 
-                let _ = tokio::fs::remove_file(destination).await;
+            let chunk = match timeout(STALL_TIMEOUT, stream.next()).await {
+                Ok(Some(Ok(chunk))) => chunk,
+                Ok(Some(Err(e))) => {
+                    break LauncherError::from(e).prefixed("Error downloading file");
+                }
+                Ok(None) if !has_content_length || downloaded_bytes >= total_size => {
+                    complete = true;
+                    continue 'request;
+                }
+                Ok(None) => {
+                    break LauncherError::new(
+                        ErrorCode::Network,
+                        "Connection closed before the download finished"
+                    );
+                }
+                Err(_) => {
+                    break stall_error();
+                }
+            };
+
+            download_manager.throttle(&build_id, chunk.len() as u64).await;
+            file.write_all(&chunk).await?;
+            downloaded_bytes += chunk.len() as u64;
+            failures = 0;
+
+            if last_update.elapsed().as_millis() > (UPDATE_INTERVAL_MS as u128) {
+                let speed = download_manager.update_speed_data(&build_id, downloaded_bytes).await;
+
+                let (percentage, eta) = if has_content_length && total_size > 0 {
+                    let percentage: f64 = ((downloaded_bytes as f64) / (total_size as f64)) * 100.0;
+                    let remaining_bytes = total_size.saturating_sub(downloaded_bytes);
+                    let eta_seconds = if speed > 0.0 {
+                        (remaining_bytes as f64) / speed
+                    } else {
+                        f64::INFINITY
+                    };
+                    (percentage, format_time(eta_seconds))
+                } else {
+                    (0.0, "Unknown".to_string())
+                };
+
+                download_manager.emit_progress(&window, DownloadProgress {
+                    build_id: build_id.clone(),
+                    percentage,
+                    downloaded_bytes,
+                    total_bytes: total_size,
+                    speed,
+                    eta,
+                    paused: false,
+                    rate_limit: download_manager.rate_limit(&build_id).await,
+                }).await;
 
-                return Err(format!("Error downloading file: {}", e).into());
+                last_update = std::time::Instant::now();
             }
+        };
+
+        // Keep what arrived; the next request asks for the rest.
+        file.flush().await?;
+
+        failures += 1;
+        if failures >= MAX_RETRIES {
+            return Err(
+                error.prefixed(
+                    format!("Failed to download after {} attempts. Last error", MAX_RETRIES)
+                )
+            );
         }
+        tokio::time::sleep(Duration::from_millis(RETRY_DELAY_MS * (failures as u64))).await;
     }
 
     file.flush().await?;
@@ -697,35 +2989,217 @@ async fn download_file(
     let file_size = tokio::fs::metadata(destination).await?.len();
     if file_size == 0 {
         let _ = tokio::fs::remove_file(destination).await;
-        return Err("Downloaded file is empty. The download may have failed.".into());
+        PartialDownload::remove(destination).await;
+        return Err(
+            LauncherError::corrupt_data("Downloaded file is empty. The download may have failed.")
+        );
     }
 
-    if has_content_length && file_size != total_size {
+    if total_size > 0 && file_size != total_size {
         let _ = tokio::fs::remove_file(destination).await;
+        PartialDownload::remove(destination).await;
         return Err(
-            format!(
-                "Downloaded file size ({}) doesn't match expected size ({}). The download may be incomplete.",
-                file_size,
-                total_size
-            ).into()
+            LauncherError::corrupt_data(
+                format!(
+                    "Downloaded file size ({}) doesn't match expected size ({}). The download may be incomplete.",
+                    file_size,
+                    total_size
+                )
+            )
         );
     }
 
-    let _ = window.emit("download:completed", build_id);
+    PartialDownload::remove(destination).await;
 
     Ok(())
 }
 
+/// Requests the part of `url` that `destination` is still missing. When the body starts
+/// from byte zero instead, the file is emptied and `downloaded_bytes` reset to match.
+/// A server that rejects the range, usually with 416 because its copy got shorter,
+/// can never resume this partial file, so it is thrown away with its resume data and
+/// the whole file is requested again.
+async fn request_rest(
+    client: &Client,
+    url: &str,
+    destination: &str,
+    file: &mut AsyncFile,
+    downloaded_bytes: &mut u64,
+    partial: &mut Option<PartialDownload>
+) -> Result<reqwest::Response, LauncherError> {
+    let if_range = partial.as_ref().and_then(PartialDownload::if_range);
+    let mut res = request_with_retries(client, url, *downloaded_bytes, if_range).await?;
+
+    if *downloaded_bytes > 0 && res.status().is_client_error() {
+        PartialDownload::remove(destination).await;
+        *partial = None;
+        res = request_with_retries(client, url, 0, None).await?;
+    }
+
+    if *downloaded_bytes > 0 && res.status() != StatusCode::PARTIAL_CONTENT {
+        // The server ignored the range or the file changed, so the body starts from
+        // byte zero again.
+        file.set_len(0).await?;
+        file.seek(SeekFrom::Start(0)).await?;
+        *downloaded_bytes = 0;
+    }
+
+    Ok(res)
+}
+
+/// Sends a GET for `url`, asking for everything from `offset` onwards when it is
+/// non-zero, and retries server and network errors. With `if_range` the server sends
+/// the whole file instead if it no longer matches that validator. A client error for
+/// a ranged request is handed back as it is, so the caller can start over without it.
+async fn request_with_retries(
+    client: &Client,
+    url: &str,
+    offset: u64,
+    if_range: Option<&str>
+) -> Result<reqwest::Response, LauncherError> {
+    let mut retries = 0;
+    let mut last_error = LauncherError::internal("No download attempts were made");
+
+    while retries < MAX_RETRIES {
+        let mut request = client.get(url);
+        if offset > 0 {
+            request = request.header(RANGE, format!("bytes={}-", offset));
+            if let Some(if_range) = if_range {
+                request = request.header(IF_RANGE, if_range);
+            }
+        }
+
+        match request.send().await {
+            Ok(res) => {
+                if res.status().is_success() || (offset > 0 && res.status().is_client_error()) {
+                    return Ok(res);
+                } else if res.status().is_server_error() {
+                    last_error = LauncherError::http_status(
+                        res.status(),
+                        url,
+                        format!("Server error: HTTP {}", res.status())
+                    );
+                } else {
+                    return Err(
+                        LauncherError::http_status(
+                            res.status(),
+                            url,
+                            format!("Failed to download file: HTTP {}", res.status())
+                        )
+                    );
+                }
+            }
+            Err(e) => {
+                last_error = LauncherError::from(e).prefixed("Network error");
+            }
+        }
+
+        retries += 1;
+        if retries < MAX_RETRIES {
+            tokio::time::sleep(Duration::from_millis(RETRY_DELAY_MS * (retries as u64))).await;
+        }
+    }
+
+    Err(
+        last_error.prefixed(
+            format!("Failed to download after {} attempts. Last error", MAX_RETRIES)
+        )
+    )
+}
+
 #[command]
-pub fn get_default_install_dir() -> Result<String, String> {
+pub fn get_default_install_dir() -> Result<String, LauncherError> {
     let home_dir = dirs
         ::home_dir()
-        .ok_or_else(|| "Could not determine home directory".to_string())?;
+        .ok_or_else(|| LauncherError::not_found("Could not determine home directory"))?;
     let default_dir = home_dir.join("Solaris").join("Builds");
 
     if !default_dir.exists() {
-        std::fs::create_dir_all(&default_dir).map_err(|e| e.to_string())?;
+        std::fs::create_dir_all(&default_dir)?;
     }
 
     Ok(default_dir.to_string_lossy().to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderMap;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    const BODY: &[u8] = b"the file as the server has it now";
+
+    /// Answers ranged requests with 416, as a server whose copy of the file got
+    /// shorter does, and anything else with the whole file.
+    async fn serve_shortened_file(listener: TcpListener) {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                match socket.read(&mut buffer).await {
+                    Ok(0) | Err(_) => {
+                        break;
+                    }
+                    Ok(read) => request.extend_from_slice(&buffer[..read]),
+                }
+            }
+
+            let ranged = String::from_utf8_lossy(&request)
+                .to_ascii_lowercase()
+                .contains("\r\nrange:");
+            let response = if ranged {
+                b"HTTP/1.1 416 Range Not Satisfiable\r\nContent-Length: 0\r\n\r\n".to_vec()
+            } else {
+                let mut response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n",
+                    BODY.len()
+                ).into_bytes();
+                response.extend_from_slice(BODY);
+                response
+            };
+            let _ = socket.write_all(&response).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn starts_over_when_the_server_rejects_a_stale_range() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/build.zip", listener.local_addr().unwrap());
+        tokio::spawn(serve_shortened_file(listener));
+
+        let dir = std::env::temp_dir().join(format!("solaris-range-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let destination = dir.join("build.zip.download").to_string_lossy().to_string();
+
+        // Left behind by an attempt at the longer file, with no validators to send.
+        fs::write(&destination, b"the first part of the file the server used to have").unwrap();
+        PartialDownload::new(&url, 96, &HeaderMap::new()).save(&destination).await.unwrap();
+
+        let mut file = tokio::fs::OpenOptions::new().write(true).open(&destination).await.unwrap();
+        file.seek(SeekFrom::End(0)).await.unwrap();
+        let mut downloaded_bytes = fs::metadata(&destination).unwrap().len();
+        let mut partial = PartialDownload::load(&destination, &url).await;
+        assert!(partial.is_some());
+
+        let res = request_rest(
+            &Client::new(),
+            &url,
+            &destination,
+            &mut file,
+            &mut downloaded_bytes,
+            &mut partial
+        ).await.unwrap();
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.bytes().await.unwrap().as_ref(), BODY);
+        assert_eq!(downloaded_bytes, 0);
+        assert_eq!(file.stream_position().await.unwrap(), 0);
+        assert_eq!(fs::metadata(&destination).unwrap().len(), 0);
+        assert!(partial.is_none());
+        assert!(PartialDownload::load(&destination, &url).await.is_none());
+
+        drop(file);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,592 @@
+use flate2::read::ZlibDecoder;
+use sha1::{ Digest, Sha1 };
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Read;
+
+use crate::error::LauncherError;
+
+const MANIFEST_MAGIC: u32 = 0x44bec00c;
+const CHUNK_MAGIC: u32 = 0xb1fe3aa2;
+/// `stored_as` flags, shared by manifests and chunk files.
+const STORED_COMPRESSED: u8 = 0x01;
+const STORED_ENCRYPTED: u8 = 0x02;
+/// Headers this long or longer end with the manifest's feature level.
+const HEADER_SIZE_WITH_VERSION: usize = 41;
+/// Feature levels at which the chunk folder on the CDN was renamed.
+const FEATURE_DATA_FILE_RENAMES: u32 = 3;
+const FEATURE_CHUNK_COMPRESSION: u32 = 6;
+const FEATURE_VARIABLE_SIZE_CHUNKS: u32 = 15;
+/// Refuse to inflate a manifest past this, so a corrupt header can't ask for gigabytes.
+const MAX_MANIFEST_SIZE: usize = 512 * 1024 * 1024;
+/// Same for a single chunk; Epic's chunks are a megabyte, variable-size ones a few more.
+const MAX_CHUNK_SIZE: usize = 64 * 1024 * 1024;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Guid([u32; 4]);
+
+impl fmt::Display for Guid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for part in self.0 {
+            write!(f, "{:08X}", part)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ChunkInfo {
+    pub guid: Guid,
+    /// The rolling hash the chunk's file on the CDN is named after.
+    pub hash: u64,
+    /// SHA-1 of the decompressed chunk, when the manifest records one.
+    pub sha1: Option<[u8; 20]>,
+    pub group: u8,
+    /// Size of the chunk once decompressed.
+    pub window_size: u32,
+}
+
+/// A slice of a chunk that makes up part of a file.
+#[derive(Clone, Debug)]
+pub struct ChunkPart {
+    pub guid: Guid,
+    pub offset: u32,
+    pub size: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct EpicFile {
+    pub name: String,
+    /// SHA-1 of the finished file; all zeroes when the manifest doesn't have one.
+    pub sha1: [u8; 20],
+    /// The file's contents, in order.
+    pub parts: Vec<ChunkPart>,
+}
+
+impl EpicFile {
+    pub fn size(&self) -> u64 {
+        self.parts
+            .iter()
+            .map(|part| part.size as u64)
+            .sum()
+    }
+}
+
+/// Running SHA-1 of a file being rebuilt, to check against `EpicFile::sha1`.
+#[derive(Default)]
+pub struct FileHasher(Sha1);
+
+impl FileHasher {
+    pub fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    /// Files the manifest has no hash for always match.
+    pub fn matches(self, file: &EpicFile) -> bool {
+        file.sha1 == [0u8; 20] || self.0.finalize()[..] == file.sha1[..]
+    }
+}
+
+/// An Epic Games binary manifest: which chunks a build is made of and how its files
+/// are cut out of them.
+#[derive(Debug)]
+pub struct EpicManifest {
+    pub feature_level: u32,
+    pub app_name: String,
+    pub build_version: String,
+    pub launch_exe: String,
+    pub chunks: HashMap<Guid, ChunkInfo>,
+    pub files: Vec<EpicFile>,
+}
+
+impl EpicManifest {
+    /// Parses a binary manifest: a header, the optionally zlib-compressed body, then
+    /// the meta, chunk data list and file manifest list sections within it.
+    pub fn parse(data: &[u8]) -> Result<Self, LauncherError> {
+        let mut header = Reader::new(data, "Epic manifest");
+        if header.u32()? != MANIFEST_MAGIC {
+            return Err(LauncherError::unsupported("Not an Epic binary manifest"));
+        }
+
+        let header_size = header.u32()? as usize;
+        let uncompressed_size = header.u32()? as usize;
+        let compressed_size = header.u32()? as usize;
+        let expected_sha1 = header.array::<20>()?;
+        let stored_as = header.u8()?;
+        let mut feature_level = if header_size >= HEADER_SIZE_WITH_VERSION {
+            header.u32()?
+        } else {
+            0
+        };
+
+        if stored_as & STORED_ENCRYPTED != 0 {
+            return Err(LauncherError::unsupported("Encrypted Epic manifests are not supported"));
+        }
+
+        let body = if stored_as & STORED_COMPRESSED != 0 {
+            let compressed = data
+                .get(header_size..header_size.saturating_add(compressed_size))
+                .ok_or_else(|| truncated("Epic manifest"))?;
+            inflate(compressed, uncompressed_size.min(MAX_MANIFEST_SIZE), "Epic manifest")?
+        } else {
+            data
+                .get(header_size..header_size.saturating_add(uncompressed_size))
+                .ok_or_else(|| truncated("Epic manifest"))?
+                .to_vec()
+        };
+
+        if Sha1::digest(&body)[..] != expected_sha1[..] {
+            return Err(LauncherError::corrupt_data("Epic manifest failed hash verification"));
+        }
+
+        let mut reader = Reader::new(&body, "Epic manifest");
+
+        let mut meta = reader.section()?;
+        let meta_feature_level = meta.u32()?;
+        if feature_level == 0 {
+            feature_level = meta_feature_level;
+        }
+        let _is_file_data = meta.u8()?;
+        let _app_id = meta.u32()?;
+        let app_name = meta.string()?;
+        let build_version = meta.string()?;
+        let launch_exe = meta.string()?;
+
+        let mut chunk_list = reader.section()?;
+        let count = chunk_list.count()?;
+        let guids = chunk_list.repeat(count, Reader::guid)?;
+        let hashes = chunk_list.repeat(count, Reader::u64)?;
+        let sha1s = chunk_list.repeat(count, Reader::array::<20>)?;
+        let groups = chunk_list.repeat(count, Reader::u8)?;
+        let window_sizes = chunk_list.repeat(count, Reader::u32)?;
+
+        let mut chunks = HashMap::with_capacity(count);
+        for index in 0..count {
+            let sha1 = Some(sha1s[index]).filter(|sha1| sha1.iter().any(|&byte| byte != 0));
+            chunks.insert(guids[index], ChunkInfo {
+                guid: guids[index],
+                hash: hashes[index],
+                sha1,
+                group: groups[index],
+                window_size: window_sizes[index],
+            });
+        }
+
+        let mut file_list = reader.section()?;
+        let count = file_list.count()?;
+        let names = file_list.repeat(count, Reader::string)?;
+        let _symlink_targets = file_list.repeat(count, Reader::string)?;
+        let sha1s = file_list.repeat(count, Reader::array::<20>)?;
+        let _flags = file_list.repeat(count, Reader::u8)?;
+        for _ in 0..count {
+            let tags = file_list.count()?;
+            file_list.repeat(tags, Reader::string)?;
+        }
+
+        let mut files = Vec::with_capacity(count);
+        for (name, sha1) in names.into_iter().zip(sha1s) {
+            let part_count = file_list.count()?;
+            let mut parts = Vec::with_capacity(part_count);
+
+            for _ in 0..part_count {
+                let mut part = file_list.sized()?;
+                let part = ChunkPart {
+                    guid: part.guid()?,
+                    offset: part.u32()?,
+                    size: part.u32()?,
+                };
+                if !chunks.contains_key(&part.guid) {
+                    return Err(
+                        LauncherError::corrupt_data(
+                            format!("{} uses chunk {}, which isn't listed", name, part.guid)
+                        )
+                    );
+                }
+                parts.push(part);
+            }
+
+            files.push(EpicFile { name, sha1, parts });
+        }
+
+        Ok(Self {
+            feature_level,
+            app_name,
+            build_version,
+            launch_exe,
+            chunks,
+            files,
+        })
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.files.iter().map(EpicFile::size).sum()
+    }
+
+    /// Path of a chunk's file relative to the chunk base URL. The folder name depends
+    /// on the feature level the build was published with.
+    pub fn chunk_path(&self, chunk: &ChunkInfo) -> String {
+        let dir = if self.feature_level >= FEATURE_VARIABLE_SIZE_CHUNKS {
+            "ChunksV4"
+        } else if self.feature_level >= FEATURE_CHUNK_COMPRESSION {
+            "ChunksV3"
+        } else if self.feature_level >= FEATURE_DATA_FILE_RENAMES {
+            "ChunksV2"
+        } else {
+            "Chunks"
+        };
+
+        format!("{}/{:02}/{:016X}_{}.chunk", dir, chunk.group, chunk.hash, chunk.guid)
+    }
+}
+
+/// Unpacks a downloaded chunk file, checking it is the chunk that was asked for and,
+/// when the manifest has its hash, that it arrived intact.
+pub fn decode_chunk(data: &[u8], chunk: &ChunkInfo) -> Result<Vec<u8>, LauncherError> {
+    let mut header = Reader::new(data, "Epic chunk");
+    if header.u32()? != CHUNK_MAGIC {
+        return Err(
+            LauncherError::corrupt_data(format!("Chunk {} is not an Epic chunk", chunk.guid))
+        );
+    }
+
+    let _version = header.u32()?;
+    let header_size = header.u32()? as usize;
+    let data_size = header.u32()? as usize;
+    let guid = header.guid()?;
+    let _hash = header.u64()?;
+    let stored_as = header.u8()?;
+
+    if guid != chunk.guid {
+        return Err(
+            LauncherError::corrupt_data(
+                format!("Expected chunk {} but received chunk {}", chunk.guid, guid)
+            )
+        );
+    }
+    if stored_as & STORED_ENCRYPTED != 0 {
+        return Err(LauncherError::unsupported("Encrypted Epic chunks are not supported"));
+    }
+
+    let body = data
+        .get(header_size..header_size.saturating_add(data_size))
+        .ok_or_else(|| truncated("Epic chunk"))?;
+    let decoded = if stored_as & STORED_COMPRESSED != 0 {
+        inflate(body, (chunk.window_size as usize).min(MAX_CHUNK_SIZE), "Epic chunk")?
+    } else {
+        body.to_vec()
+    };
+
+    if let Some(expected) = chunk.sha1 {
+        if Sha1::digest(&decoded)[..] != expected[..] {
+            return Err(
+                LauncherError::corrupt_data(
+                    format!("Chunk {} failed hash verification", chunk.guid)
+                )
+            );
+        }
+    }
+
+    Ok(decoded)
+}
+
+/// The bytes of `chunk` that `part` refers to.
+pub fn part_data<'a>(chunk: &'a [u8], part: &ChunkPart) -> Result<&'a [u8], LauncherError> {
+    let start = part.offset as usize;
+    chunk.get(start..start + (part.size as usize)).ok_or_else(|| {
+        LauncherError::corrupt_data(
+            format!("Chunk {} is too short for the part of it the manifest uses", part.guid)
+        )
+    })
+}
+
+fn inflate(data: &[u8], limit: usize, what: &str) -> Result<Vec<u8>, LauncherError> {
+    let mut decoded = Vec::with_capacity(limit);
+    ZlibDecoder::new(data)
+        .take((limit as u64) + 1)
+        .read_to_end(&mut decoded)
+        .map_err(|e| LauncherError::corrupt_data(format!("Failed to decompress {}: {}", what, e)))?;
+
+    if decoded.len() > limit {
+        return Err(LauncherError::corrupt_data(format!("{} is larger than its header says", what)));
+    }
+
+    Ok(decoded)
+}
+
+fn truncated(what: &str) -> LauncherError {
+    LauncherError::corrupt_data(format!("{} is truncated", what))
+}
+
+/// Little-endian cursor over manifest and chunk data.
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+    what: &'static str,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8], what: &'static str) -> Self {
+        Self { data, position: 0, what }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], LauncherError> {
+        let bytes = self.data
+            .get(self.position..self.position.saturating_add(len))
+            .ok_or_else(|| truncated(self.what))?;
+        self.position += len;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], LauncherError> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.bytes(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> Result<u8, LauncherError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, LauncherError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, LauncherError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn guid(&mut self) -> Result<Guid, LauncherError> {
+        Ok(Guid([self.u32()?, self.u32()?, self.u32()?, self.u32()?]))
+    }
+
+    /// An array length, checked against what is left so a corrupt count fails here
+    /// instead of in a huge allocation.
+    fn count(&mut self) -> Result<usize, LauncherError> {
+        let count = self.u32()? as usize;
+        if count > self.data.len() - self.position {
+            return Err(truncated(self.what));
+        }
+        Ok(count)
+    }
+
+    fn repeat<T>(
+        &mut self,
+        count: usize,
+        mut read: impl FnMut(&mut Self) -> Result<T, LauncherError>
+    ) -> Result<Vec<T>, LauncherError> {
+        (0..count).map(|_| read(self)).collect()
+    }
+
+    /// An Unreal `FString`: a length that counts the terminating nul, negative for
+    /// UTF-16 text.
+    fn string(&mut self) -> Result<String, LauncherError> {
+        let len = self.u32()? as i32;
+        let text = if len >= 0 {
+            String::from_utf8_lossy(self.bytes(len as usize)?).into_owned()
+        } else {
+            let units: Vec<u16> = self
+                .bytes((len.unsigned_abs() as usize).saturating_mul(2))?
+                .chunks_exact(2)
+                .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        };
+
+        Ok(text.trim_end_matches('\0').to_string())
+    }
+
+    /// A struct that starts with its own size, returned as a reader over just that
+    /// struct. This reader moves past all of it, so fields added by newer feature
+    /// levels are skipped.
+    fn sized(&mut self) -> Result<Reader<'a>, LauncherError> {
+        let start = self.position;
+        let size = self.u32()? as usize;
+        let data = self.data
+            .get(start..start.saturating_add(size))
+            .ok_or_else(|| truncated(self.what))?;
+        self.position = start + size;
+
+        Ok(Reader { data, position: 4, what: self.what })
+    }
+
+    /// A top-level manifest section: a sized struct that starts with a version byte.
+    /// Every version only appends fields, so the byte is skipped.
+    fn section(&mut self) -> Result<Reader<'a>, LauncherError> {
+        let mut section = self.sized()?;
+        section.u8()?;
+        Ok(section)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::ZlibEncoder;
+    use std::io::Write;
+
+    const CHUNK: Guid = Guid([1, 2, 3, 4]);
+    const OTHER_CHUNK: Guid = Guid([5, 6, 7, 8]);
+
+    fn put_u32(out: &mut Vec<u8>, value: u32) {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn put_guid(out: &mut Vec<u8>, guid: Guid) {
+        for part in guid.0 {
+            put_u32(out, part);
+        }
+    }
+
+    fn put_string(out: &mut Vec<u8>, text: &str) {
+        put_u32(out, (text.len() as u32) + 1);
+        out.extend_from_slice(text.as_bytes());
+        out.push(0);
+    }
+
+    /// Wraps `body` the way `Reader::section` expects: its size, then a version byte.
+    fn section(body: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        put_u32(&mut out, (body.len() as u32) + 5);
+        out.push(0);
+        out.extend_from_slice(body);
+        out
+    }
+
+    fn sha1(data: &[u8]) -> [u8; 20] {
+        let mut hash = [0u8; 20];
+        hash.copy_from_slice(&Sha1::digest(data)[..]);
+        hash
+    }
+
+    fn manifest_body(chunk_sha1: [u8; 20], file_sha1: [u8; 20]) -> Vec<u8> {
+        let mut meta = Vec::new();
+        put_u32(&mut meta, 18);
+        meta.push(0);
+        put_u32(&mut meta, 0);
+        put_string(&mut meta, "Fortnite");
+        put_string(&mut meta, "++Fortnite+Release-12.41-CL-12905909");
+        put_string(&mut meta, "FortniteGame/Binaries/Win64/FortniteLauncher.exe");
+
+        let mut chunk_list = Vec::new();
+        put_u32(&mut chunk_list, 2);
+        put_guid(&mut chunk_list, CHUNK);
+        put_guid(&mut chunk_list, OTHER_CHUNK);
+        chunk_list.extend_from_slice(&0x1122334455667788u64.to_le_bytes());
+        chunk_list.extend_from_slice(&7u64.to_le_bytes());
+        chunk_list.extend_from_slice(&chunk_sha1);
+        chunk_list.extend_from_slice(&[0u8; 20]);
+        chunk_list.extend_from_slice(&[3, 4]);
+        put_u32(&mut chunk_list, 11);
+        put_u32(&mut chunk_list, 1024);
+
+        let mut file_list = Vec::new();
+        put_u32(&mut file_list, 1);
+        put_string(&mut file_list, "FortniteGame/Content/Paks/test.pak");
+        put_string(&mut file_list, "");
+        file_list.extend_from_slice(&file_sha1);
+        file_list.push(0);
+        put_u32(&mut file_list, 0);
+        put_u32(&mut file_list, 2);
+        for (offset, size) in [(6, 5), (0, 5)] {
+            put_u32(&mut file_list, 28);
+            put_guid(&mut file_list, CHUNK);
+            put_u32(&mut file_list, offset);
+            put_u32(&mut file_list, size);
+        }
+
+        [section(&meta), section(&chunk_list), section(&file_list)].concat()
+    }
+
+    fn manifest(body: &[u8], compress: bool) -> Vec<u8> {
+        let stored = if compress {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(body).unwrap();
+            encoder.finish().unwrap()
+        } else {
+            body.to_vec()
+        };
+
+        let mut out = Vec::new();
+        put_u32(&mut out, MANIFEST_MAGIC);
+        put_u32(&mut out, HEADER_SIZE_WITH_VERSION as u32);
+        put_u32(&mut out, body.len() as u32);
+        put_u32(&mut out, stored.len() as u32);
+        out.extend_from_slice(&sha1(body));
+        out.push(if compress { STORED_COMPRESSED } else { 0 });
+        put_u32(&mut out, 18);
+        out.extend_from_slice(&stored);
+        out
+    }
+
+    fn chunk_file(guid: Guid, data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        let stored = encoder.finish().unwrap();
+
+        let mut out = Vec::new();
+        put_u32(&mut out, CHUNK_MAGIC);
+        put_u32(&mut out, 3);
+        put_u32(&mut out, 41);
+        put_u32(&mut out, stored.len() as u32);
+        put_guid(&mut out, guid);
+        out.extend_from_slice(&0x1122334455667788u64.to_le_bytes());
+        out.push(STORED_COMPRESSED);
+        out.extend_from_slice(&stored);
+        out
+    }
+
+    #[test]
+    fn parses_a_manifest_and_rebuilds_its_file() {
+        let chunk_data = b"hello world";
+        let file_data = b"worldhello";
+        let body = manifest_body(sha1(chunk_data), sha1(file_data));
+
+        for compress in [false, true] {
+            let manifest = EpicManifest::parse(&manifest(&body, compress)).unwrap();
+
+            assert_eq!(manifest.feature_level, 18);
+            assert_eq!(manifest.app_name, "Fortnite");
+            assert_eq!(manifest.build_version, "++Fortnite+Release-12.41-CL-12905909");
+            assert_eq!(manifest.chunks.len(), 2);
+            assert_eq!(manifest.chunks[&OTHER_CHUNK].sha1, None);
+            assert_eq!(manifest.total_size(), 10);
+
+            let chunk = &manifest.chunks[&CHUNK];
+            assert_eq!(chunk.group, 3);
+            assert_eq!(chunk.window_size, 11);
+            assert_eq!(
+                manifest.chunk_path(chunk),
+                "ChunksV4/03/1122334455667788_00000001000000020000000300000004.chunk"
+            );
+
+            let decoded = decode_chunk(&chunk_file(CHUNK, chunk_data), chunk).unwrap();
+            let file = &manifest.files[0];
+            let mut rebuilt = Vec::new();
+            let mut hasher = FileHasher::default();
+            for part in &file.parts {
+                let data = part_data(&decoded, part).unwrap();
+                hasher.update(data);
+                rebuilt.extend_from_slice(data);
+            }
+
+            assert_eq!(rebuilt, file_data);
+            assert!(hasher.matches(file));
+        }
+    }
+
+    #[test]
+    fn rejects_damaged_manifests_and_chunks() {
+        let body = manifest_body(sha1(b"hello world"), [0u8; 20]);
+        let mut data = manifest(&body, false);
+        let last = data.len() - 1;
+        data[last] ^= 0xff;
+        assert!(EpicManifest::parse(&data).is_err());
+        assert!(EpicManifest::parse(&data[..30]).is_err());
+
+        let manifest = EpicManifest::parse(&manifest(&body, false)).unwrap();
+        let chunk = &manifest.chunks[&CHUNK];
+        assert!(decode_chunk(&chunk_file(CHUNK, b"hello there"), chunk).is_err());
+        assert!(decode_chunk(&chunk_file(OTHER_CHUNK, b"hello world"), chunk).is_err());
+    }
+}
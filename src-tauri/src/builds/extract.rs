@@ -0,0 +1,355 @@
+use flate2::read::GzDecoder;
+use std::fs::{ self, File };
+use std::io::{ self, BufReader, Read };
+use std::path::{ Component, Path, PathBuf };
+use std::sync::Arc;
+use std::sync::atomic::{ AtomicU64, Ordering };
+
+use crate::error::{ LauncherError, ResultExt };
+
+/// A tar header says `ustar` at byte 257.
+const TAR_MAGIC_OFFSET: usize = 257;
+const TAR_MAGIC_END: usize = TAR_MAGIC_OFFSET + 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+    SevenZip,
+    Rar,
+}
+
+impl ArchiveKind {
+    /// Works out the format from the file name, falling back to the file's leading
+    /// bytes when the name says nothing, e.g. a `.tar.gz` saved as `<id>.gz`.
+    pub fn detect(path: &Path) -> Option<Self> {
+        Self::from_name(path).or_else(|| Self::sniff(path).ok().flatten())
+    }
+
+    fn from_name(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+
+        if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(Self::Tar)
+        } else if name.ends_with(".7z") {
+            Some(Self::SevenZip)
+        } else if name.ends_with(".rar") {
+            Some(Self::Rar)
+        } else {
+            None
+        }
+    }
+
+    fn sniff(path: &Path) -> io::Result<Option<Self>> {
+        let mut header = Vec::with_capacity(TAR_MAGIC_END);
+        File::open(path)?.take(TAR_MAGIC_END as u64).read_to_end(&mut header)?;
+
+        Ok(
+            if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+                Some(Self::Zip)
+            } else if header.starts_with(b"7z\xBC\xAF\x27\x1C") {
+                Some(Self::SevenZip)
+            } else if header.starts_with(b"Rar!\x1A\x07") {
+                Some(Self::Rar)
+            } else if is_tar_header(&header) {
+                Some(Self::Tar)
+            } else if header.starts_with(&[0x1f, 0x8b]) {
+                // Only a gzipped tar is an archive; a single gzipped file is left alone.
+                let mut inner = Vec::with_capacity(TAR_MAGIC_END);
+                GzDecoder::new(File::open(path)?)
+                    .take(TAR_MAGIC_END as u64)
+                    .read_to_end(&mut inner)?;
+                is_tar_header(&inner).then_some(Self::TarGz)
+            } else {
+                None
+            }
+        )
+    }
+
+    /// Folder an archive is unpacked into: the archive path without its extension, or
+    /// with `-extracted` added when it has none.
+    pub fn target_dir(self, archive: &Path) -> PathBuf {
+        let name = archive
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let stem = if self == Self::TarGz && name.to_lowercase().ends_with(".tar.gz") {
+            &name[..name.len() - ".tar.gz".len()]
+        } else {
+            match name.rfind('.') {
+                Some(dot) if dot > 0 => &name[..dot],
+                _ => "",
+            }
+        };
+
+        if stem.is_empty() {
+            archive.with_file_name(format!("{}-extracted", name))
+        } else {
+            archive.with_file_name(stem)
+        }
+    }
+}
+
+fn is_tar_header(header: &[u8]) -> bool {
+    header.get(TAR_MAGIC_OFFSET..TAR_MAGIC_END) == Some(b"ustar".as_slice())
+}
+
+/// Where an extraction currently is, handed to the progress callback after each entry.
+pub struct ExtractionStep<'a> {
+    pub current_file: &'a str,
+    pub processed_files: usize,
+    /// Zero for tar streams, which don't list their entries up front.
+    pub total_files: usize,
+    pub fraction: f64,
+}
+
+/// Unpacks `archive` into `target`. `on_entry` runs after every entry and returns
+/// false to stop early, in which case the extraction fails with "Extraction cancelled".
+pub fn extract_archive(
+    kind: ArchiveKind,
+    archive: &Path,
+    target: &Path,
+    mut on_entry: impl FnMut(ExtractionStep) -> bool
+) -> Result<(), LauncherError> {
+    fs::create_dir_all(target).context(format!("Failed to create {}", target.display()))?;
+
+    match kind {
+        ArchiveKind::Zip => extract_zip(archive, target, &mut on_entry),
+        ArchiveKind::Tar => {
+            let file = open_archive(archive)?;
+            extract_tar(file, archive, target, false, &mut on_entry)
+        }
+        ArchiveKind::TarGz => {
+            let file = open_archive(archive)?;
+            extract_tar(file, archive, target, true, &mut on_entry)
+        }
+        ArchiveKind::SevenZip => extract_7z(archive, target, &mut on_entry),
+        ArchiveKind::Rar =>
+            Err(
+                LauncherError::unsupported(
+                    "RAR archives are not supported, please extract this build manually"
+                )
+            ),
+    }
+}
+
+fn open_archive(archive: &Path) -> Result<File, LauncherError> {
+    File::open(archive).context("Failed to open archive")
+}
+
+fn extract_zip(
+    archive: &Path,
+    target: &Path,
+    on_entry: &mut impl FnMut(ExtractionStep) -> bool
+) -> Result<(), LauncherError> {
+    let file = open_archive(archive)?;
+    let mut zip = zip::ZipArchive
+        ::new(BufReader::new(file))
+        .map_err(|e| LauncherError::corrupt_data(format!("Failed to read zip archive: {}", e)))?;
+
+    let total_files = zip.len();
+
+    for index in 0..total_files {
+        let mut entry = zip
+            .by_index(index)
+            .map_err(|e| LauncherError::corrupt_data(format!("Failed to read zip entry: {}", e)))?;
+
+        let relative_path = entry
+            .enclosed_name()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| {
+                LauncherError::corrupt_data(
+                    format!("Refusing to extract unsafe path {}", entry.name())
+                )
+            })?;
+        let out_path = target.join(&relative_path);
+
+        if entry.is_dir() {
+            fs::create_dir_all(&out_path)?;
+        } else {
+            write_entry(&mut entry, &out_path)?;
+        }
+
+        let step = ExtractionStep {
+            current_file: &relative_path.to_string_lossy(),
+            processed_files: index + 1,
+            total_files,
+            fraction: ((index + 1) as f64) / (total_files as f64),
+        };
+        if !on_entry(step) {
+            return Err(LauncherError::cancelled("Extraction cancelled"));
+        }
+    }
+
+    Ok(())
+}
+
+fn extract_tar(
+    file: File,
+    archive: &Path,
+    target: &Path,
+    gzipped: bool,
+    on_entry: &mut impl FnMut(ExtractionStep) -> bool
+) -> Result<(), LauncherError> {
+    // Tar has no index, so progress is measured by how much of the archive has been read.
+    let archive_size = file
+        .metadata()
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+    let bytes_read = Arc::new(AtomicU64::new(0));
+    let reader = CountingReader {
+        inner: BufReader::new(file),
+        bytes_read: bytes_read.clone(),
+    };
+
+    let reader: Box<dyn Read> = if gzipped { Box::new(GzDecoder::new(reader)) } else { Box::new(reader) };
+    let mut tar = tar::Archive::new(reader);
+
+    let entries = tar
+        .entries()
+        .context(format!("Failed to read {}", archive.display()))?;
+
+    for (index, entry) in entries.enumerate() {
+        let mut entry = entry.context("Failed to read tar entry")?;
+        let relative_path = entry
+            .path()
+            .map(|path| path.to_path_buf())
+            .context("Invalid tar entry path")?;
+
+        // `unpack_in` refuses entries that would land outside `target`.
+        entry
+            .unpack_in(target)
+            .context(format!("Failed to extract {}", relative_path.display()))?;
+
+        let fraction = if archive_size > 0 {
+            ((bytes_read.load(Ordering::Relaxed) as f64) / (archive_size as f64)).min(1.0)
+        } else {
+            0.0
+        };
+
+        let step = ExtractionStep {
+            current_file: &relative_path.to_string_lossy(),
+            processed_files: index + 1,
+            total_files: 0,
+            fraction,
+        };
+        if !on_entry(step) {
+            return Err(LauncherError::cancelled("Extraction cancelled"));
+        }
+    }
+
+    Ok(())
+}
+
+fn extract_7z(
+    archive: &Path,
+    target: &Path,
+    on_entry: &mut impl FnMut(ExtractionStep) -> bool
+) -> Result<(), LauncherError> {
+    let mut reader = sevenz_rust::SevenZReader
+        ::open(archive, sevenz_rust::Password::empty())
+        .map_err(|e| LauncherError::corrupt_data(format!("Failed to read 7z archive: {}", e)))?;
+
+    let total_files = reader.archive().files.len();
+    let mut processed_files = 0;
+    let mut cancelled = false;
+    let mut entry_error = None;
+
+    let result = reader.for_each_entries(|entry, data| {
+        let relative_path = match safe_relative_path(entry.name()) {
+            Some(path) => path,
+            None => {
+                entry_error = Some(
+                    LauncherError::corrupt_data(
+                        format!("Refusing to extract unsafe path {}", entry.name())
+                    )
+                );
+                return Ok(false);
+            }
+        };
+        let out_path = target.join(&relative_path);
+
+        let written = if entry.is_directory() {
+            fs::create_dir_all(&out_path).map_err(LauncherError::from)
+        } else {
+            write_entry(data, &out_path)
+        };
+        if let Err(e) = written {
+            entry_error = Some(e);
+            return Ok(false);
+        }
+
+        processed_files += 1;
+        let step = ExtractionStep {
+            current_file: &relative_path.to_string_lossy(),
+            processed_files,
+            total_files,
+            fraction: (processed_files as f64) / (total_files.max(1) as f64),
+        };
+        if !on_entry(step) {
+            cancelled = true;
+            return Ok(false);
+        }
+
+        Ok(true)
+    });
+
+    if let Some(e) = entry_error {
+        return Err(e);
+    }
+    if cancelled {
+        return Err(LauncherError::cancelled("Extraction cancelled"));
+    }
+
+    result.map_err(|e| LauncherError::corrupt_data(format!("Failed to extract 7z archive: {}", e)))
+}
+
+fn write_entry(data: &mut dyn Read, out_path: &Path) -> Result<(), LauncherError> {
+    if let Some(parent) = out_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut out_file = File::create(out_path).context(
+        format!("Failed to create {}", out_path.display())
+    )?;
+    io::copy(data, &mut out_file).context(format!("Failed to write {}", out_path.display()))?;
+
+    Ok(())
+}
+
+/// Rejects absolute paths and `..` so an entry can't escape the target folder.
+fn safe_relative_path(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    let mut relative = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            _ => {
+                return None;
+            }
+        }
+    }
+
+    Some(relative)
+}
+
+struct CountingReader<R> {
+    inner: R,
+    bytes_read: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.bytes_read.fetch_add(read as u64, Ordering::Relaxed);
+        Ok(read)
+    }
+}
@@ -0,0 +1,210 @@
+use serde::{ Deserialize, Serialize };
+use sha2::{ Digest, Sha256 };
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::{ SystemTime, UNIX_EPOCH };
+
+/// Progress of the file that was being written when the install stopped.
+/// Chunks are always written in order, so `completed_chunks` is the number of
+/// leading entries of `ChunkedFile::chunks_ids` already in the `.part` file.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FileProgress {
+    pub file: String,
+    pub completed_chunks: usize,
+    pub bytes_written: u64,
+}
+
+/// On-disk record of a manifest install, stored outside the install directory so it
+/// survives launcher restarts and lets `download_build` continue where it stopped.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InstallJournal {
+    pub build_id: String,
+    pub version: String,
+    pub destination: String,
+    pub install_path: String,
+    pub total_bytes: u64,
+    pub completed_bytes: u64,
+    pub completed_files: HashSet<String>,
+    pub current_file: Option<FileProgress>,
+    pub updated_at: u64,
+    /// Journals for one-off jobs such as repairs are tracked in memory only.
+    #[serde(skip)]
+    ephemeral: bool,
+}
+
+#[derive(Serialize)]
+pub struct ResumableInstall {
+    build_id: String,
+    version: String,
+    destination: String,
+    completed_bytes: u64,
+    total_bytes: u64,
+    percentage: f64,
+    updated_at: u64,
+}
+
+impl InstallJournal {
+    /// Loads the journal for `build_id` if it describes the same install, otherwise
+    /// starts a fresh one.
+    pub async fn open_or_create(
+        build_id: &str,
+        version: &str,
+        destination: &str,
+        install_path: &str
+    ) -> Self {
+        if let Some(journal) = Self::load(build_id).await {
+            if
+                journal.version == version &&
+                journal.destination == destination &&
+                journal.install_path == install_path
+            {
+                return journal;
+            }
+        }
+
+        Self {
+            build_id: build_id.to_string(),
+            version: version.to_string(),
+            destination: destination.to_string(),
+            install_path: install_path.to_string(),
+            total_bytes: 0,
+            completed_bytes: 0,
+            completed_files: HashSet::new(),
+            current_file: None,
+            updated_at: unix_now(),
+            ephemeral: false,
+        }
+    }
+
+    /// A journal that is never written to disk, for installs that write straight into
+    /// an existing build rather than a `.download` directory.
+    pub fn ephemeral(build_id: &str, version: &str, install_path: &str, total_bytes: u64) -> Self {
+        Self {
+            build_id: build_id.to_string(),
+            version: version.to_string(),
+            destination: install_path.to_string(),
+            install_path: install_path.to_string(),
+            total_bytes,
+            completed_bytes: 0,
+            completed_files: HashSet::new(),
+            current_file: None,
+            updated_at: unix_now(),
+            ephemeral: true,
+        }
+    }
+
+    pub async fn load(build_id: &str) -> Option<Self> {
+        let data = tokio::fs::read(journal_path(build_id)?).await.ok()?;
+        let journal: Self = serde_json::from_slice(&data).ok()?;
+        (journal.build_id == build_id).then_some(journal)
+    }
+
+    /// Writes the journal through a temp file so a crash mid-write never leaves it corrupt.
+    pub async fn save(&mut self) -> std::io::Result<()> {
+        if self.ephemeral {
+            return Ok(());
+        }
+
+        let path = journal_path(&self.build_id).ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "Could not determine home directory")
+        })?;
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        self.updated_at = unix_now();
+        let data = serde_json::to_vec(self)?;
+        let temp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&temp_path, data).await?;
+        tokio::fs::rename(&temp_path, &path).await
+    }
+
+    pub async fn remove(build_id: &str) {
+        if let Some(path) = journal_path(build_id) {
+            let _ = tokio::fs::remove_file(path).await;
+        }
+    }
+
+    pub async fn list() -> Vec<Self> {
+        let mut journals = Vec::new();
+
+        let Some(dir) = journal_dir() else {
+            return journals;
+        };
+
+        let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+            return journals;
+        };
+
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+
+            if let Ok(data) = tokio::fs::read(&path).await {
+                if let Ok(journal) = serde_json::from_slice::<InstallJournal>(&data) {
+                    journals.push(journal);
+                }
+            }
+        }
+
+        journals
+    }
+
+    pub fn summary(&self) -> ResumableInstall {
+        let percentage = if self.total_bytes > 0 {
+            ((self.completed_bytes as f64) / (self.total_bytes as f64)) * 100.0
+        } else {
+            0.0
+        };
+
+        ResumableInstall {
+            build_id: self.build_id.clone(),
+            version: self.version.clone(),
+            destination: self.destination.clone(),
+            completed_bytes: self.completed_bytes,
+            total_bytes: self.total_bytes,
+            percentage,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+fn journal_dir() -> Option<PathBuf> {
+    Some(dirs::home_dir()?.join("Solaris").join("Journals"))
+}
+
+/// Journals are named after a hash of the build id, which can hold any character and
+/// might otherwise collide on case-insensitive file systems. The id itself is kept
+/// inside the journal.
+fn journal_path(build_id: &str) -> Option<PathBuf> {
+    Some(journal_dir()?.join(journal_file_name(build_id)))
+}
+
+fn journal_file_name(build_id: &str) -> String {
+    format!("{:x}.json", Sha256::digest(build_id.as_bytes()))
+}
+
+pub(super) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gives_every_build_its_own_journal() {
+        let ids = ["a b", "a_b", "a/b", "A_B", "Release-12.41", "release-12.41", ""];
+        let names: HashSet<String> = ids.iter().map(|id| journal_file_name(id)).collect();
+
+        assert_eq!(names.len(), ids.len());
+        assert_eq!(journal_file_name("a b"), journal_file_name("a b"));
+        assert!(names.iter().all(|name| name.ends_with(".json") && !name.contains('/')));
+    }
+}
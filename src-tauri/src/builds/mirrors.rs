@@ -0,0 +1,151 @@
+use futures_util::future::join_all;
+use reqwest::Client;
+use serde::Serialize;
+use std::time::{ Duration, Instant };
+use tauri::command;
+
+use crate::error::{ LauncherError, ResultExt };
+
+use super::network;
+use super::settings::{ DownloadSettings, normalize_mirrors };
+use super::version::BuildVersion;
+
+const HEALTH_CHECK_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MirrorKind {
+    Manifest,
+    Version,
+}
+
+#[derive(Serialize)]
+pub struct MirrorHealth {
+    url: String,
+    kind: MirrorKind,
+    reachable: bool,
+    status: Option<u16>,
+    latency_ms: Option<u64>,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct MirrorList {
+    manifest_mirrors: Vec<String>,
+    version_mirrors: Vec<String>,
+    epic_chunk_base_url: Option<String>,
+}
+
+impl From<DownloadSettings> for MirrorList {
+    fn from(settings: DownloadSettings) -> Self {
+        Self {
+            manifest_mirrors: settings.manifest_mirrors,
+            version_mirrors: settings.version_mirrors,
+            epic_chunk_base_url: settings.epic_chunk_base_url,
+        }
+    }
+}
+
+/// Times a HEAD request to `url`. With `require_success` only a 2xx counts as
+/// reachable, otherwise any answer short of a server error does.
+async fn probe(
+    client: &Client,
+    mirror: &str,
+    url: String,
+    kind: MirrorKind,
+    require_success: bool
+) -> MirrorHealth {
+    let start = Instant::now();
+
+    match client.head(&url).send().await {
+        Ok(response) => {
+            let status = response.status();
+            let reachable = if require_success {
+                status.is_success()
+            } else {
+                !status.is_server_error()
+            };
+
+            MirrorHealth {
+                url: mirror.to_string(),
+                kind,
+                reachable,
+                status: Some(status.as_u16()),
+                latency_ms: Some(start.elapsed().as_millis() as u64),
+                error: if reachable { None } else { Some(format!("HTTP {}", status)) },
+            }
+        }
+        Err(e) =>
+            MirrorHealth {
+                url: mirror.to_string(),
+                kind,
+                reachable: false,
+                status: None,
+                latency_ms: None,
+                error: Some(e.to_string()),
+            },
+    }
+}
+
+#[command]
+pub async fn get_mirrors() -> Result<MirrorList, LauncherError> {
+    Ok(DownloadSettings::load().await.into())
+}
+
+/// Replaces the mirror lists. Each list is tried in order, so the first entry is the
+/// preferred host. Leaving out the Epic chunk host clears it.
+#[command]
+pub async fn set_mirrors(
+    manifest_mirrors: Vec<String>,
+    version_mirrors: Vec<String>,
+    epic_chunk_base_url: Option<String>
+) -> Result<MirrorList, LauncherError> {
+    let mut settings = DownloadSettings::load().await;
+    settings.manifest_mirrors = normalize_mirrors(manifest_mirrors)?;
+    settings.version_mirrors = normalize_mirrors(version_mirrors)?;
+    settings.epic_chunk_base_url = match epic_chunk_base_url {
+        Some(url) if !url.trim().is_empty() => normalize_mirrors(vec![url])?.pop(),
+        _ => None,
+    };
+
+    settings.save().await.context("Failed to save mirrors")?;
+
+    Ok(settings.into())
+}
+
+/// Checks every configured mirror at once. Manifest mirrors are probed for the given
+/// version's manifest when one is passed, otherwise just for a response.
+#[command]
+pub async fn check_mirrors(
+    version: Option<String>
+) -> Result<Vec<MirrorHealth>, LauncherError> {
+    let settings = DownloadSettings::load().await;
+
+    let client = network
+        ::client_builder().await?
+        .timeout(Duration::from_secs(HEALTH_CHECK_TIMEOUT_SECS))
+        .build()
+        .context("Failed to create HTTP client")?;
+
+    let release = match &version {
+        Some(version) => Some(BuildVersion::parse(version)?.manifest_name()),
+        None => None,
+    };
+
+    let manifest_checks = settings.manifest_mirrors.iter().map(|mirror| {
+        let (url, require_success) = match &release {
+            Some(release) => (format!("{}/{}/{}.manifest", mirror, release, release), true),
+            None => (mirror.clone(), false),
+        };
+        probe(&client, mirror, url, MirrorKind::Manifest, require_success)
+    });
+
+    let version_checks = settings.version_mirrors.iter().map(|mirror| {
+        probe(&client, mirror, format!("{}/versions.json", mirror), MirrorKind::Version, true)
+    });
+
+    let mut results = join_all(manifest_checks).await;
+    results.extend(join_all(version_checks).await);
+
+    Ok(results)
+}
@@ -1 +1,19 @@
+pub mod catalogue;
+pub mod checksum;
+pub mod chunk_cache;
+mod decompress;
+mod epic_manifest;
+pub mod disk_space;
 pub mod download_manager;
+mod extract;
+mod journal;
+pub mod mirrors;
+pub mod network;
+mod partial;
+mod queue;
+mod response_cache;
+mod settings;
+mod state;
+mod throttle;
+pub mod version;
+pub mod verify;
@@ -0,0 +1,159 @@
+use reqwest::{ Certificate, Client, ClientBuilder, NoProxy, Proxy };
+use serde::Serialize;
+use tauri::command;
+
+use super::settings::{ DownloadSettings, normalize_proxy };
+use crate::error::{ LauncherError, ResultExt };
+
+#[derive(Serialize)]
+pub struct NetworkSettings {
+    proxy: Option<String>,
+    no_proxy: Vec<String>,
+    ca_certificates: Vec<String>,
+}
+
+impl From<DownloadSettings> for NetworkSettings {
+    fn from(settings: DownloadSettings) -> Self {
+        Self {
+            proxy: settings.proxy,
+            no_proxy: settings.no_proxy,
+            ca_certificates: settings.ca_certificates,
+        }
+    }
+}
+
+/// The proxy and extra root certificates from the download settings, ready to apply to
+/// a client. Every HTTP client the launcher builds goes through this, so requests
+/// behave the same whichever command makes them.
+pub struct NetworkConfig {
+    proxy: Option<Proxy>,
+    certificates: Vec<Certificate>,
+}
+
+impl NetworkConfig {
+    pub async fn load() -> Result<Self, LauncherError> {
+        Self::from_settings(&DownloadSettings::load().await).await
+    }
+
+    /// `load` for the synchronous commands, which can't await.
+    pub fn load_blocking() -> Result<Self, LauncherError> {
+        let settings = DownloadSettings::load_blocking();
+
+        let mut pems = Vec::new();
+        for path in &settings.ca_certificates {
+            let pem = std::fs::read(path).context(format!("Failed to read certificate {}", path))?;
+            pems.push((path.as_str(), pem));
+        }
+
+        Self::build(&settings, pems)
+    }
+
+    async fn from_settings(settings: &DownloadSettings) -> Result<Self, LauncherError> {
+        let mut pems = Vec::new();
+        for path in &settings.ca_certificates {
+            let pem = tokio::fs
+                ::read(path).await
+                .context(format!("Failed to read certificate {}", path))?;
+            pems.push((path.as_str(), pem));
+        }
+
+        Self::build(settings, pems)
+    }
+
+    fn build(
+        settings: &DownloadSettings,
+        pems: Vec<(&str, Vec<u8>)>
+    ) -> Result<Self, LauncherError> {
+        let proxy = match &settings.proxy {
+            Some(url) => {
+                let no_proxy = NoProxy::from_string(&settings.no_proxy.join(","));
+                Some(Proxy::all(url).context("Invalid proxy")?.no_proxy(no_proxy))
+            }
+            None => None,
+        };
+
+        let mut certificates = Vec::new();
+        for (path, pem) in pems {
+            certificates.extend(
+                Certificate::from_pem_bundle(&pem).context(
+                    format!("Failed to parse certificate {}", path)
+                )?
+            );
+        }
+
+        Ok(Self { proxy, certificates })
+    }
+
+    pub fn apply(self, mut builder: ClientBuilder) -> ClientBuilder {
+        if let Some(proxy) = self.proxy {
+            builder = builder.proxy(proxy);
+        }
+        for certificate in self.certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+        builder
+    }
+
+    pub fn apply_blocking(
+        self,
+        mut builder: reqwest::blocking::ClientBuilder
+    ) -> reqwest::blocking::ClientBuilder {
+        if let Some(proxy) = self.proxy {
+            builder = builder.proxy(proxy);
+        }
+        for certificate in self.certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+        builder
+    }
+}
+
+/// `Client::builder()` with the configured proxy and certificates already applied.
+pub async fn client_builder() -> Result<ClientBuilder, LauncherError> {
+    Ok(NetworkConfig::load().await?.apply(Client::builder()))
+}
+
+/// `Client::new()` with the configured proxy and certificates.
+pub async fn client() -> Result<Client, LauncherError> {
+    client_builder().await?.build().context("Failed to create HTTP client")
+}
+
+pub fn blocking_client() -> Result<reqwest::blocking::Client, LauncherError> {
+    NetworkConfig::load_blocking()?
+        .apply_blocking(reqwest::blocking::Client::builder())
+        .build()
+        .context("Failed to create HTTP client")
+}
+
+#[command]
+pub async fn get_network_settings() -> Result<NetworkSettings, LauncherError> {
+    Ok(DownloadSettings::load().await.into())
+}
+
+/// Replaces the proxy and certificate settings. They are checked by loading them
+/// before anything is saved, so a typo can't leave the launcher unable to connect.
+#[command]
+pub async fn set_network_settings(
+    proxy: Option<String>,
+    no_proxy: Vec<String>,
+    ca_certificates: Vec<String>
+) -> Result<NetworkSettings, LauncherError> {
+    let mut settings = DownloadSettings::load().await;
+    settings.proxy = normalize_proxy(proxy)?;
+    settings.no_proxy = trimmed(no_proxy);
+    settings.ca_certificates = trimmed(ca_certificates);
+
+    NetworkConfig::from_settings(&settings).await?;
+
+    settings.save().await.context("Failed to save network settings")?;
+
+    Ok(settings.into())
+}
+
+fn trimmed(values: Vec<String>) -> Vec<String> {
+    values
+        .into_iter()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
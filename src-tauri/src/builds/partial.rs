@@ -0,0 +1,84 @@
+use reqwest::header::{ ETAG, HeaderMap, LAST_MODIFIED };
+use serde::{ Deserialize, Serialize };
+use std::path::PathBuf;
+
+/// What is known about an interrupted direct download, saved beside its `.download`
+/// file so a later attempt, even after a restart, only asks for what is missing.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PartialDownload {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub total_size: u64,
+    /// Each range of a segmented download as (next byte to fetch, end). Empty for a
+    /// single stream, whose progress is simply the length of the file.
+    pub segments: Vec<(u64, u64)>,
+}
+
+impl PartialDownload {
+    pub fn new(url: &str, total_size: u64, headers: &HeaderMap) -> Self {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+
+        Self {
+            url: url.to_string(),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            total_size,
+            segments: Vec::new(),
+        }
+    }
+
+    /// The saved state for `destination`, if it was downloading the same URL.
+    pub async fn load(destination: &str, url: &str) -> Option<Self> {
+        let data = tokio::fs::read(sidecar_path(destination)).await.ok()?;
+        let partial: Self = serde_json::from_slice(&data).ok()?;
+        (partial.url == url).then_some(partial)
+    }
+
+    /// Writes through a temp file so a crash mid-write never leaves it corrupt.
+    pub async fn save(&self, destination: &str) -> std::io::Result<()> {
+        let path = sidecar_path(destination);
+        let data = serde_json::to_vec(self)?;
+        let temp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&temp_path, data).await?;
+        tokio::fs::rename(&temp_path, &path).await
+    }
+
+    pub async fn remove(destination: &str) {
+        let _ = tokio::fs::remove_file(sidecar_path(destination)).await;
+    }
+
+    /// Bytes a segmented download still has to fetch.
+    pub fn remaining_bytes(&self) -> u64 {
+        self.segments
+            .iter()
+            .map(|(position, end)| end - position)
+            .sum()
+    }
+
+    /// Value for an `If-Range` header, so the server sends the whole file instead of
+    /// a range when it has changed. Weak ETags aren't allowed there, in which case the
+    /// modification date is used.
+    pub fn if_range(&self) -> Option<&str> {
+        self.etag
+            .as_deref()
+            .filter(|etag| !etag.starts_with("W/"))
+            .or(self.last_modified.as_deref())
+    }
+
+    /// Whether `other`, built from a fresh response, describes the same file.
+    pub fn same_resource(&self, other: &Self) -> bool {
+        self.total_size == other.total_size &&
+            self.if_range().is_some() &&
+            self.if_range() == other.if_range()
+    }
+}
+
+fn sidecar_path(destination: &str) -> PathBuf {
+    PathBuf::from(format!("{}.resume.json", destination))
+}
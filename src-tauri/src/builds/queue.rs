@@ -0,0 +1,133 @@
+use serde::Serialize;
+
+pub const DEFAULT_MAX_CONCURRENT_DOWNLOADS: usize = 1;
+pub const MAX_CONCURRENT_DOWNLOADS: usize = 8;
+
+struct QueuedDownload {
+    build_id: String,
+    priority: i32,
+}
+
+#[derive(Clone, Serialize)]
+pub struct QueueEntry {
+    build_id: String,
+    position: usize,
+    priority: i32,
+}
+
+#[derive(Clone, Serialize)]
+pub struct QueuePosition {
+    pub build_id: String,
+    pub position: usize,
+}
+
+/// Builds waiting to download, in the order they will start, plus the ones running.
+/// Paused builds give up their slot and are kept aside until they resume.
+/// Positions handed to and from the frontend are 1-based.
+pub struct DownloadQueue {
+    waiting: Vec<QueuedDownload>,
+    running: Vec<QueuedDownload>,
+    paused: Vec<QueuedDownload>,
+    max_concurrent: usize,
+}
+
+impl DownloadQueue {
+    pub fn new() -> Self {
+        Self {
+            waiting: Vec::new(),
+            running: Vec::new(),
+            paused: Vec::new(),
+            max_concurrent: DEFAULT_MAX_CONCURRENT_DOWNLOADS,
+        }
+    }
+
+    /// Adds a build behind everything already waiting with the same or higher priority.
+    pub fn enqueue(&mut self, build_id: &str, priority: i32) {
+        let index = self.waiting
+            .iter()
+            .position(|queued| queued.priority < priority)
+            .unwrap_or(self.waiting.len());
+
+        self.waiting.insert(index, QueuedDownload {
+            build_id: build_id.to_string(),
+            priority,
+        });
+    }
+
+    /// Moves `build_id` from the queue to the running set if there is a free slot for
+    /// it once everything ahead of it has started.
+    pub fn try_start(&mut self, build_id: &str) -> bool {
+        let free_slots = self.max_concurrent.saturating_sub(self.running.len());
+
+        match self.index_of(build_id) {
+            Some(index) if index < free_slots => {
+                let queued = self.waiting.remove(index);
+                self.running.push(queued);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Frees the slot of a running build that was paused so the next one can start.
+    pub fn pause(&mut self, build_id: &str) {
+        if let Some(index) = self.running.iter().position(|queued| queued.build_id == build_id) {
+            let queued = self.running.remove(index);
+            self.paused.push(queued);
+        }
+    }
+
+    /// Puts a paused build back at the front of the queue to take the next free slot.
+    /// Returns false if it wasn't holding a slot when it paused.
+    pub fn resume(&mut self, build_id: &str) -> bool {
+        let Some(index) = self.paused.iter().position(|queued| queued.build_id == build_id) else {
+            return false;
+        };
+
+        let queued = self.paused.remove(index);
+        self.waiting.insert(0, queued);
+        true
+    }
+
+    pub fn position(&self, build_id: &str) -> Option<usize> {
+        self.index_of(build_id).map(|index| index + 1)
+    }
+
+    pub fn remove(&mut self, build_id: &str) {
+        self.waiting.retain(|queued| queued.build_id != build_id);
+        self.running.retain(|queued| queued.build_id != build_id);
+        self.paused.retain(|queued| queued.build_id != build_id);
+    }
+
+    pub fn move_to(&mut self, build_id: &str, position: usize) -> bool {
+        let Some(index) = self.index_of(build_id) else {
+            return false;
+        };
+
+        let queued = self.waiting.remove(index);
+        let target = position.saturating_sub(1).min(self.waiting.len());
+        self.waiting.insert(target, queued);
+        true
+    }
+
+    pub fn set_max_concurrent(&mut self, max_concurrent: usize) -> usize {
+        self.max_concurrent = max_concurrent.clamp(1, MAX_CONCURRENT_DOWNLOADS);
+        self.max_concurrent
+    }
+
+    pub fn entries(&self) -> Vec<QueueEntry> {
+        self.waiting
+            .iter()
+            .enumerate()
+            .map(|(index, queued)| QueueEntry {
+                build_id: queued.build_id.clone(),
+                position: index + 1,
+                priority: queued.priority,
+            })
+            .collect()
+    }
+
+    fn index_of(&self, build_id: &str) -> Option<usize> {
+        self.waiting.iter().position(|queued| queued.build_id == build_id)
+    }
+}
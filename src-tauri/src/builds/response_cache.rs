@@ -0,0 +1,139 @@
+use reqwest::RequestBuilder;
+use reqwest::header::{ ETAG, HeaderMap, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED };
+use serde::de::DeserializeOwned;
+use serde::{ Deserialize, Serialize };
+use std::path::PathBuf;
+
+use super::journal::unix_now;
+
+/// A command result that may have come from the on-disk cache. `stale` is set when
+/// every mirror failed and the last saved copy was served instead.
+#[derive(Clone, Serialize)]
+pub struct Cached<T> {
+    pub data: T,
+    pub stale: bool,
+    /// Unix seconds of the last time a mirror confirmed this copy.
+    pub fetched_at: u64,
+}
+
+impl<T> Cached<T> {
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Cached<U> {
+        Cached {
+            data: f(self.data),
+            stale: self.stale,
+            fetched_at: self.fetched_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheMeta {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    fetched_at: u64,
+}
+
+/// A saved response body and the validators it was served with.
+pub struct CachedResponse {
+    body: Vec<u8>,
+    meta: CacheMeta,
+}
+
+impl CachedResponse {
+    /// Adds `If-None-Match`/`If-Modified-Since` so an unchanged resource comes back as
+    /// a bodiless 304.
+    pub fn conditional(&self, mut request: RequestBuilder) -> RequestBuilder {
+        if let Some(etag) = &self.meta.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &self.meta.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+        request
+    }
+
+    pub fn parse<T: DeserializeOwned>(&self) -> Option<T> {
+        serde_json::from_slice(&self.body).ok()
+    }
+
+    pub fn fetched_at(&self) -> u64 {
+        self.meta.fetched_at
+    }
+}
+
+/// One cached resource under `~/Solaris/Cache`, kept as the raw body plus a small
+/// metadata file. Failing to read or write the cache never fails the request.
+pub struct ResponseCache {
+    paths: Option<(PathBuf, PathBuf)>,
+}
+
+impl ResponseCache {
+    pub fn new(key: &str) -> Self {
+        let paths = dirs::home_dir().map(|home| {
+            let dir = home.join("Solaris").join("Cache");
+            (dir.join(format!("{}.json", key)), dir.join(format!("{}.meta.json", key)))
+        });
+
+        Self { paths }
+    }
+
+    pub async fn load(&self) -> Option<CachedResponse> {
+        let (body_path, meta_path) = self.paths.as_ref()?;
+
+        let meta = serde_json::from_slice(&tokio::fs::read(meta_path).await.ok()?).ok()?;
+        let body = tokio::fs::read(body_path).await.ok()?;
+
+        Some(CachedResponse { body, meta })
+    }
+
+    /// Saves a fresh 200 response along with its validators.
+    pub async fn store(&self, body: &[u8], headers: &HeaderMap) {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let meta = CacheMeta {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            fetched_at: unix_now(),
+        };
+
+        let Some((body_path, meta_path)) = &self.paths else {
+            return;
+        };
+        if let Some(parent) = body_path.parent() {
+            if tokio::fs::create_dir_all(parent).await.is_err() {
+                return;
+            }
+        }
+
+        // Drop the old validators first so they can never be paired with the new body.
+        let _ = tokio::fs::remove_file(meta_path).await;
+
+        let temp_path = body_path.with_extension("json.tmp");
+        if tokio::fs::write(&temp_path, body).await.is_err() {
+            return;
+        }
+        if tokio::fs::rename(&temp_path, body_path).await.is_ok() {
+            self.write_meta(&meta).await;
+        }
+    }
+
+    /// Records that a mirror answered 304 for `cached`, returning the new fetch time.
+    pub async fn revalidated(&self, cached: &mut CachedResponse) -> u64 {
+        cached.meta.fetched_at = unix_now();
+        self.write_meta(&cached.meta).await;
+        cached.meta.fetched_at
+    }
+
+    async fn write_meta(&self, meta: &CacheMeta) {
+        let Some((_, meta_path)) = &self.paths else {
+            return;
+        };
+        if let Ok(data) = serde_json::to_vec(meta) {
+            let _ = tokio::fs::write(meta_path, data).await;
+        }
+    }
+}
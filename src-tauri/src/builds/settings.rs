@@ -0,0 +1,146 @@
+use serde::{ Deserialize, Serialize };
+use std::path::PathBuf;
+
+use crate::error::LauncherError;
+
+pub const DEFAULT_MANIFEST_MIRROR: &str = "https://manifest.simplyblk.xyz";
+pub const DEFAULT_VERSION_MIRROR: &str = "https://cdn.solarisfn.dev";
+/// The chunk cache is off until the user gives it a size limit.
+pub const DEFAULT_CHUNK_CACHE_MAX_BYTES: u64 = 0;
+
+/// Download settings that persist between launcher sessions, stored in
+/// `~/Solaris/download_settings.json`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct DownloadSettings {
+    /// Hosts serving manifests and chunks, in the order they are tried.
+    pub manifest_mirrors: Vec<String>,
+    /// Hosts serving `versions.json`, in the order they are tried.
+    pub version_mirrors: Vec<String>,
+    /// Size limit of the shared chunk cache. Zero turns the cache off.
+    pub chunk_cache_max_bytes: u64,
+    /// Proxy for every request the launcher makes, as an `http://`, `https://` or
+    /// `socks5://` URL with optional credentials. None falls back to the system's
+    /// `HTTP_PROXY`/`HTTPS_PROXY` variables.
+    pub proxy: Option<String>,
+    /// Hosts that skip the proxy, in the usual `NO_PROXY` format.
+    pub no_proxy: Vec<String>,
+    /// PEM files with extra root certificates to trust, for proxies that inspect TLS.
+    pub ca_certificates: Vec<String>,
+    /// Where chunks for Epic binary manifests are fetched from when a download doesn't
+    /// name a host itself.
+    pub epic_chunk_base_url: Option<String>,
+}
+
+impl Default for DownloadSettings {
+    fn default() -> Self {
+        Self {
+            manifest_mirrors: vec![DEFAULT_MANIFEST_MIRROR.to_string()],
+            version_mirrors: vec![DEFAULT_VERSION_MIRROR.to_string()],
+            chunk_cache_max_bytes: DEFAULT_CHUNK_CACHE_MAX_BYTES,
+            proxy: None,
+            no_proxy: Vec::new(),
+            ca_certificates: Vec::new(),
+            epic_chunk_base_url: None,
+        }
+    }
+}
+
+impl DownloadSettings {
+    /// Reads the saved settings, falling back to the defaults for anything missing or
+    /// unreadable.
+    pub async fn load() -> Self {
+        let data = match settings_path() {
+            Some(path) => tokio::fs::read(&path).await.ok(),
+            None => None,
+        };
+        Self::parse(data)
+    }
+
+    /// `load` for the synchronous commands, which can't await.
+    pub fn load_blocking() -> Self {
+        Self::parse(settings_path().and_then(|path| std::fs::read(path).ok()))
+    }
+
+    fn parse(data: Option<Vec<u8>>) -> Self {
+        let mut settings: Self = data
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+
+        if settings.manifest_mirrors.is_empty() {
+            settings.manifest_mirrors = Self::default().manifest_mirrors;
+        }
+        if settings.version_mirrors.is_empty() {
+            settings.version_mirrors = Self::default().version_mirrors;
+        }
+
+        settings
+    }
+
+    pub async fn save(&self) -> std::io::Result<()> {
+        let path = settings_path().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "Could not determine home directory")
+        })?;
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let data = serde_json::to_vec_pretty(self)?;
+        let temp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&temp_path, data).await?;
+        tokio::fs::rename(&temp_path, &path).await
+    }
+}
+
+/// Trims and de-duplicates a mirror list, rejecting anything that isn't an http(s) URL.
+pub fn normalize_mirrors(mirrors: Vec<String>) -> Result<Vec<String>, LauncherError> {
+    let mut normalized: Vec<String> = Vec::new();
+
+    for mirror in mirrors {
+        let mirror = mirror.trim().trim_end_matches('/').to_string();
+        if mirror.is_empty() {
+            continue;
+        }
+        if !mirror.starts_with("https://") && !mirror.starts_with("http://") {
+            return Err(
+                LauncherError::invalid_input(
+                    format!("Mirror must be an http or https URL: {}", mirror)
+                ).with_context("mirror", mirror)
+            );
+        }
+        if !normalized.contains(&mirror) {
+            normalized.push(mirror);
+        }
+    }
+
+    if normalized.is_empty() {
+        return Err(LauncherError::invalid_input("At least one mirror is required"));
+    }
+
+    Ok(normalized)
+}
+
+/// Checks that a proxy URL uses a scheme reqwest can talk to. An empty string clears
+/// the proxy.
+pub fn normalize_proxy(proxy: Option<String>) -> Result<Option<String>, LauncherError> {
+    let Some(proxy) = proxy.map(|proxy| proxy.trim().to_string()) else {
+        return Ok(None);
+    };
+    if proxy.is_empty() {
+        return Ok(None);
+    }
+
+    let supported = ["http://", "https://", "socks5://", "socks5h://"];
+    if !supported.iter().any(|scheme| proxy.starts_with(scheme)) {
+        return Err(
+            LauncherError::invalid_input("Proxy must be an http, https, socks5 or socks5h URL")
+        );
+    }
+
+    Ok(Some(proxy))
+}
+
+fn settings_path() -> Option<PathBuf> {
+    Some(dirs::home_dir()?.join("Solaris").join("download_settings.json"))
+}
@@ -0,0 +1,128 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use super::download_manager::DownloadProgress;
+use super::journal::unix_now;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum DownloadState {
+    Queued {
+        position: Option<usize>,
+    },
+    Downloading,
+    Paused,
+    Verifying,
+    Extracting,
+    Completed,
+    Failed {
+        reason: String,
+    },
+}
+
+impl DownloadState {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed { .. })
+    }
+}
+
+/// Everything the frontend needs to redraw one build after a reload. Timestamps are
+/// unix seconds.
+#[derive(Clone, Serialize)]
+pub struct DownloadSnapshot {
+    build_id: String,
+    #[serde(flatten)]
+    state: DownloadState,
+    /// Last numbers sent as `download:progress`.
+    progress: Option<DownloadProgress>,
+    /// Percentage of the verify or extraction pass currently running.
+    step_percentage: Option<f64>,
+    created_at: u64,
+    started_at: Option<u64>,
+    updated_at: u64,
+    finished_at: Option<u64>,
+}
+
+/// Current state of every build the launcher has worked on since it started.
+/// Finished builds stay listed until the same build id is started again.
+pub struct DownloadStates {
+    downloads: HashMap<String, DownloadSnapshot>,
+}
+
+impl DownloadStates {
+    pub fn new() -> Self {
+        Self {
+            downloads: HashMap::new(),
+        }
+    }
+
+    /// Starts tracking `build_id` from scratch, dropping whatever a previous run left.
+    pub fn begin(&mut self, build_id: &str, state: DownloadState) -> DownloadSnapshot {
+        let now = unix_now();
+        let queued = matches!(state, DownloadState::Queued { .. });
+        let snapshot = DownloadSnapshot {
+            build_id: build_id.to_string(),
+            started_at: if queued { None } else { Some(now) },
+            state,
+            progress: None,
+            step_percentage: None,
+            created_at: now,
+            updated_at: now,
+            finished_at: None,
+        };
+
+        self.downloads.insert(build_id.to_string(), snapshot.clone());
+        snapshot
+    }
+
+    /// Moves `build_id` to `state`. Returns the new snapshot, or None when the build
+    /// isn't tracked or is already in that state.
+    pub fn transition(&mut self, build_id: &str, state: DownloadState) -> Option<DownloadSnapshot> {
+        let snapshot = self.downloads.get_mut(build_id)?;
+        if snapshot.state == state {
+            return None;
+        }
+
+        let now = unix_now();
+        if snapshot.started_at.is_none() && !matches!(state, DownloadState::Queued { .. }) {
+            snapshot.started_at = Some(now);
+        }
+        if state.is_finished() {
+            snapshot.finished_at = Some(now);
+        }
+        if matches!(state, DownloadState::Downloading | DownloadState::Paused) {
+            snapshot.step_percentage = None;
+        }
+
+        snapshot.state = state;
+        snapshot.updated_at = now;
+        Some(snapshot.clone())
+    }
+
+    pub fn state(&self, build_id: &str) -> Option<&DownloadState> {
+        self.downloads.get(build_id).map(|snapshot| &snapshot.state)
+    }
+
+    pub fn record_progress(&mut self, build_id: &str, progress: DownloadProgress) {
+        if let Some(snapshot) = self.downloads.get_mut(build_id) {
+            snapshot.progress = Some(progress);
+            snapshot.updated_at = unix_now();
+        }
+    }
+
+    pub fn record_step(&mut self, build_id: &str, percentage: f64) {
+        if let Some(snapshot) = self.downloads.get_mut(build_id) {
+            snapshot.step_percentage = Some(percentage);
+            snapshot.updated_at = unix_now();
+        }
+    }
+
+    /// Every tracked build, oldest first.
+    pub fn list(&self) -> Vec<DownloadSnapshot> {
+        let mut snapshots: Vec<DownloadSnapshot> = self.downloads.values().cloned().collect();
+        snapshots.sort_by(|a, b| {
+            a.created_at.cmp(&b.created_at).then_with(|| a.build_id.cmp(&b.build_id))
+        });
+        snapshots
+    }
+}
@@ -0,0 +1,87 @@
+use std::sync::{ Mutex, MutexGuard };
+use std::time::{ Duration, Instant };
+
+/// Longest a waiting download sleeps before looking at the limit again, so raising or
+/// removing a limit takes effect straight away.
+const MAX_WAIT_MS: u64 = 250;
+
+/// Token bucket capping how many bytes per second pass through it. The bucket holds
+/// at most one second's worth of bytes, and the limit can be changed at any time.
+pub struct RateLimiter {
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    bytes_per_sec: Option<u64>,
+    available: f64,
+    last_refill: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self, bytes_per_sec: u64) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.available = (self.available + elapsed * (bytes_per_sec as f64)).min(
+            bytes_per_sec as f64
+        );
+        self.last_refill = now;
+    }
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self {
+            bucket: Mutex::new(Bucket {
+                bytes_per_sec: None,
+                available: 0.0,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn limit(&self) -> Option<u64> {
+        self.bucket().bytes_per_sec
+    }
+
+    /// Sets the limit in bytes per second. `None` or zero removes it.
+    pub fn set_limit(&self, bytes_per_sec: Option<u64>) {
+        let mut bucket = self.bucket();
+        bucket.bytes_per_sec = bytes_per_sec.filter(|limit| *limit > 0);
+        bucket.available = 0.0;
+        bucket.last_refill = Instant::now();
+    }
+
+    /// Takes `bytes` out of the bucket, then waits until the bucket is no longer in
+    /// debt. Returns immediately while no limit is set.
+    pub async fn acquire(&self, bytes: u64) {
+        {
+            let mut bucket = self.bucket();
+            let Some(bytes_per_sec) = bucket.bytes_per_sec else {
+                return;
+            };
+            bucket.refill(bytes_per_sec);
+            bucket.available -= bytes as f64;
+        }
+
+        loop {
+            let wait = {
+                let mut bucket = self.bucket();
+                let Some(bytes_per_sec) = bucket.bytes_per_sec else {
+                    return;
+                };
+                bucket.refill(bytes_per_sec);
+
+                if bucket.available >= 0.0 {
+                    return;
+                }
+                Duration::from_secs_f64(-bucket.available / (bytes_per_sec as f64))
+            };
+
+            tokio::time::sleep(wait.min(Duration::from_millis(MAX_WAIT_MS))).await;
+        }
+    }
+
+    fn bucket(&self) -> MutexGuard<'_, Bucket> {
+        self.bucket.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,328 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{ Path, PathBuf };
+use std::time::Instant;
+use tauri::{ Emitter, State, Window, command };
+
+use super::checksum;
+use super::download_manager::{
+    ChunkSource,
+    ChunkedFile,
+    DEFAULT_CHUNK_WORKERS,
+    DownloadManager,
+    MAX_CHUNK_WORKERS,
+    ManifestFile,
+    fetch_manifest,
+    install_chunked_files,
+};
+use super::journal::InstallJournal;
+use super::state::DownloadState;
+use crate::error::LauncherError;
+
+const UPDATE_INTERVAL_MS: u64 = 100;
+
+#[derive(Clone, Serialize)]
+pub struct VerifyProgress {
+    build_id: String,
+    percentage: f64,
+    checked_files: usize,
+    total_files: usize,
+    checked_bytes: u64,
+    total_bytes: u64,
+    current_file: String,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FileProblem {
+    Missing,
+    WrongSize {
+        expected: u64,
+        actual: u64,
+    },
+    WrongHash {
+        expected: String,
+        actual: String,
+    },
+}
+
+#[derive(Clone, Serialize)]
+pub struct DamagedFile {
+    file: String,
+    problem: FileProblem,
+}
+
+#[derive(Serialize)]
+pub struct VerifyReport {
+    build_id: String,
+    version: String,
+    total_files: usize,
+    damaged_files: Vec<DamagedFile>,
+    healthy: bool,
+}
+
+#[derive(Serialize)]
+pub struct RepairReport {
+    build_id: String,
+    version: String,
+    damaged_files: Vec<DamagedFile>,
+    repaired_files: Vec<String>,
+}
+
+async fn check_file(
+    path: PathBuf,
+    chunked_file: &ChunkedFile
+) -> Result<Option<FileProblem>, LauncherError> {
+    let metadata = match tokio::fs::metadata(&path).await {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(Some(FileProblem::Missing));
+        }
+        Err(e) => {
+            return Err(
+                LauncherError::from(e).prefixed(format!("Failed to read {}", path.display()))
+            );
+        }
+    };
+
+    let expected_size = chunked_file.file_size as u64;
+    if metadata.len() != expected_size {
+        return Ok(
+            Some(FileProblem::WrongSize {
+                expected: expected_size,
+                actual: metadata.len(),
+            })
+        );
+    }
+
+    if let Some(expected_hash) = &chunked_file.file_hash {
+        let actual_hash = tokio::task::spawn_blocking(move || checksum::sha256_file(&path)).await??;
+
+        if !checksum::hashes_match(expected_hash, &actual_hash) {
+            return Ok(
+                Some(FileProblem::WrongHash {
+                    expected: expected_hash.clone(),
+                    actual: actual_hash,
+                })
+            );
+        }
+    }
+
+    Ok(None)
+}
+
+/// Checks every manifest file under `install_path` for presence, size and (when the
+/// manifest carries one) hash, emitting `verify:progress` as it goes.
+async fn scan_build(
+    window: &Window,
+    build_id: &str,
+    install_path: &Path,
+    manifest: &ManifestFile,
+    download_manager: &DownloadManager
+) -> Result<Vec<DamagedFile>, LauncherError> {
+    let total_files = manifest.chunks.len();
+    let total_bytes: u64 = manifest.chunks
+        .iter()
+        .map(|chunked_file| chunked_file.file_size as u64)
+        .sum();
+
+    let mut damaged_files = Vec::new();
+    let mut checked_bytes: u64 = 0;
+    let mut last_update = Instant::now();
+
+    for (index, chunked_file) in manifest.chunks.iter().enumerate() {
+        if !download_manager.is_active(build_id).await {
+            return Err(LauncherError::cancelled("Verification cancelled"));
+        }
+
+        if let Some(problem) = check_file(install_path.join(&chunked_file.file), chunked_file).await? {
+            damaged_files.push(DamagedFile {
+                file: chunked_file.file.clone(),
+                problem,
+            });
+        }
+
+        checked_bytes += chunked_file.file_size as u64;
+        let percentage = if total_bytes > 0 {
+            ((checked_bytes as f64) / (total_bytes as f64)) * 100.0
+        } else {
+            100.0
+        };
+        download_manager.record_step(build_id, percentage).await;
+
+        if
+            last_update.elapsed().as_millis() > (UPDATE_INTERVAL_MS as u128) ||
+            index + 1 == total_files
+        {
+            let _ = window.emit("verify:progress", VerifyProgress {
+                build_id: build_id.to_string(),
+                percentage,
+                checked_files: index + 1,
+                total_files,
+                checked_bytes,
+                total_bytes,
+                current_file: chunked_file.file.clone(),
+            });
+
+            last_update = Instant::now();
+        }
+    }
+
+    Ok(damaged_files)
+}
+
+#[command]
+pub async fn verify_build(
+    window: Window,
+    build_id: String,
+    path: String,
+    version: String,
+    download_manager: State<'_, DownloadManager>
+) -> Result<VerifyReport, LauncherError> {
+    let manifest = fetch_manifest(&version).await?.data;
+
+    if !download_manager.register(&build_id).await {
+        return Err(LauncherError::already_running("Download already in progress"));
+    }
+    download_manager.begin_tracking(&window, &build_id, DownloadState::Verifying).await;
+
+    let result = scan_build(
+        &window,
+        &build_id,
+        Path::new(&path),
+        &manifest,
+        &download_manager
+    ).await;
+
+    download_manager.unregister(&build_id).await;
+
+    let final_state = match &result {
+        Ok(_) => DownloadState::Completed,
+        Err(e) => DownloadState::Failed { reason: e.message.clone() },
+    };
+    download_manager.set_state(&window, &build_id, final_state).await;
+
+    let damaged_files = result?;
+
+    Ok(VerifyReport {
+        build_id,
+        version,
+        total_files: manifest.chunks.len(),
+        healthy: damaged_files.is_empty(),
+        damaged_files,
+    })
+}
+
+/// Verifies an installed build and re-downloads only the files that are missing or
+/// wrong, writing them in place. Waits its turn in the download queue first.
+#[command]
+pub async fn repair_build(
+    window: Window,
+    build_id: String,
+    path: String,
+    version: String,
+    chunk_workers: Option<usize>,
+    download_manager: State<'_, DownloadManager>
+) -> Result<RepairReport, LauncherError> {
+    let manifest = fetch_manifest(&version).await?.data;
+
+    if !download_manager.register(&build_id).await {
+        return Err(LauncherError::already_running("Download already in progress"));
+    }
+
+    // A repair downloads like any other build, so it waits for a slot the same way.
+    download_manager.begin_tracking(
+        &window,
+        &build_id,
+        DownloadState::Queued { position: None }
+    ).await;
+    download_manager.enqueue(&build_id, 0).await;
+
+    if !download_manager.wait_for_turn(&window, &build_id).await {
+        download_manager.unregister(&build_id).await;
+        download_manager.set_state(&window, &build_id, DownloadState::Failed {
+            reason: "Repair cancelled".into(),
+        }).await;
+        let _ = window.emit("repair:failed", build_id);
+        return Err(LauncherError::cancelled("Repair cancelled"));
+    }
+    download_manager.set_state(&window, &build_id, DownloadState::Verifying).await;
+
+    let install_path = Path::new(&path);
+
+    let result = async {
+        let damaged_files = scan_build(
+            &window,
+            &build_id,
+            install_path,
+            &manifest,
+            &download_manager
+        ).await?;
+
+        let damaged_names: HashSet<&str> = damaged_files
+            .iter()
+            .map(|damaged| damaged.file.as_str())
+            .collect();
+
+        let files: Vec<&ChunkedFile> = manifest.chunks
+            .iter()
+            .filter(|chunked_file| damaged_names.contains(chunked_file.file.as_str()))
+            .collect();
+
+        if !files.is_empty() {
+            download_manager.set_state(&window, &build_id, DownloadState::Downloading).await;
+
+            let total_bytes = files
+                .iter()
+                .map(|chunked_file| chunked_file.file_size as u64)
+                .sum();
+
+            let source = ChunkSource::new(
+                &version,
+                manifest.compression,
+                chunk_workers.unwrap_or(DEFAULT_CHUNK_WORKERS).clamp(1, MAX_CHUNK_WORKERS)
+            ).await?;
+            let mut journal = InstallJournal::ephemeral(&build_id, &version, &path, total_bytes);
+
+            install_chunked_files(
+                &window,
+                &build_id,
+                &files,
+                install_path,
+                &source,
+                &mut journal,
+                &download_manager
+            ).await?;
+        }
+
+        let repaired_files = files
+            .iter()
+            .map(|chunked_file| chunked_file.file.clone())
+            .collect();
+
+        Ok::<_, LauncherError>((damaged_files, repaired_files))
+    }.await;
+
+    download_manager.unregister(&build_id).await;
+
+    match result {
+        Ok((damaged_files, repaired_files)) => {
+            download_manager.set_state(&window, &build_id, DownloadState::Completed).await;
+            let _ = window.emit("repair:completed", build_id.clone());
+
+            Ok(RepairReport {
+                build_id,
+                version,
+                damaged_files,
+                repaired_files,
+            })
+        }
+        Err(e) => {
+            download_manager.set_state(&window, &build_id, DownloadState::Failed {
+                reason: e.message.clone(),
+            }).await;
+            let _ = window.emit("repair:failed", build_id);
+            Err(e)
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use tauri::command;
+
+use crate::error::LauncherError;
+
+/// Prefix of the version strings the game itself reports.
+const GAME_PREFIX: &str = "++Fortnite+";
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Branch {
+    Release,
+    Cert,
+    Live,
+    Other(String),
+}
+
+impl Branch {
+    fn parse(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "release" => Self::Release,
+            "cert" => Self::Cert,
+            "live" => Self::Live,
+            _ => Self::Other(name.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Branch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Release => f.write_str("Release"),
+            Self::Cert => f.write_str("Cert"),
+            Self::Live => f.write_str("Live"),
+            Self::Other(name) => f.write_str(name),
+        }
+    }
+}
+
+/// `major.minor` with an optional hotfix patch, e.g. `12.41` or `2.4.2`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct ReleaseNumber {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: Option<u32>,
+}
+
+impl ReleaseNumber {
+    fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.split('.').map(|part| part.parse::<u32>().ok());
+
+        let major = parts.next()??;
+        let minor = parts.next()??;
+        let patch = match parts.next() {
+            Some(patch) => Some(patch?),
+            None => None,
+        };
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self { major, minor, patch })
+    }
+}
+
+impl fmt::Display for ReleaseNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)?;
+        if let Some(patch) = self.patch {
+            write!(f, ".{}", patch)?;
+        }
+        Ok(())
+    }
+}
+
+/// A parsed build version. Accepts the game's own `++Fortnite+Release-12.41-CL-12905909`
+/// strings as well as shorter forms typed by users, like `Release-2.4.2`, `12.41`,
+/// `Cert-CL-3807424` or `Live`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct BuildVersion {
+    pub branch: Branch,
+    /// Missing for branches such as `Cert` that are only identified by changelist.
+    pub number: Option<ReleaseNumber>,
+    pub changelist: Option<u64>,
+}
+
+impl BuildVersion {
+    pub fn parse(input: &str) -> Result<Self, LauncherError> {
+        input.parse()
+    }
+
+    /// Name of the build's folder and manifest on the manifest mirrors. The mirrors key
+    /// releases by `major.minor` only, so `Release-2.4.2` shares the `2.4` manifest.
+    /// Versions without a number, such as `Cert-CL-3807424`, use the canonical string.
+    pub fn manifest_name(&self) -> String {
+        match self.number {
+            Some(number) => format!("{}.{}", number.major, number.minor),
+            None => self.to_string(),
+        }
+    }
+}
+
+impl FromStr for BuildVersion {
+    type Err = LauncherError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            LauncherError::invalid_input(
+                format!("Unrecognised build version: {}", input)
+            ).with_context("version", input)
+        };
+
+        let trimmed = input.trim();
+        let body = trimmed.strip_prefix(GAME_PREFIX).unwrap_or(trimmed);
+        let mut tokens = body.split('-').filter(|token| !token.is_empty()).peekable();
+
+        // A leading word names the branch; a bare number is a release.
+        let mut branch = match tokens.peek() {
+            Some(token) if is_branch(token) => {
+                let branch = Branch::parse(token);
+                tokens.next();
+                branch
+            }
+            Some(_) => Branch::Release,
+            None => {
+                return Err(invalid());
+            }
+        };
+
+        // The game reports cert builds as `Release-Cert-CL-...`.
+        if branch == Branch::Release {
+            if let Some(token) = tokens.peek().filter(|token| is_branch(token)) {
+                branch = Branch::parse(token);
+                tokens.next();
+            }
+        }
+
+        let number = match tokens.peek() {
+            Some(token) if token.starts_with(|c: char| c.is_ascii_digit()) => {
+                let number = ReleaseNumber::parse(token).ok_or_else(invalid)?;
+                tokens.next();
+                Some(number)
+            }
+            _ => None,
+        };
+
+        let changelist = match tokens.peek() {
+            Some(token) if token.eq_ignore_ascii_case("CL") => {
+                tokens.next();
+                let changelist = tokens
+                    .next()
+                    .and_then(|token| token.parse::<u64>().ok())
+                    .ok_or_else(invalid)?;
+                Some(changelist)
+            }
+            _ => None,
+        };
+
+        // Anything left must be a trailing word such as a platform name.
+        if tokens.any(|token| !is_word(token)) {
+            return Err(invalid());
+        }
+        if branch == Branch::Release && number.is_none() {
+            return Err(invalid());
+        }
+
+        Ok(Self { branch, number, changelist })
+    }
+}
+
+fn is_word(token: &str) -> bool {
+    token.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+fn is_branch(token: &str) -> bool {
+    is_word(token) && !token.eq_ignore_ascii_case("CL")
+}
+
+impl fmt::Display for BuildVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.branch)?;
+        if let Some(number) = self.number {
+            write!(f, "-{}", number)?;
+        }
+        if let Some(changelist) = self.changelist {
+            write!(f, "-CL-{}", changelist)?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for BuildVersion {
+    /// Oldest first: by release number, then changelist. Versions without a number
+    /// sort before numbered ones.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.number
+            .cmp(&other.number)
+            .then_with(|| self.changelist.cmp(&other.changelist))
+            .then_with(|| self.branch.to_string().cmp(&other.branch.to_string()))
+    }
+}
+
+impl PartialOrd for BuildVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Parses a version typed by the user, so the frontend can validate it and show how it
+/// was read before requesting the build.
+#[command]
+pub fn parse_build_version(version: String) -> Result<BuildVersion, LauncherError> {
+    BuildVersion::parse(&version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest_name(input: &str) -> String {
+        BuildVersion::parse(input).unwrap().manifest_name()
+    }
+
+    #[test]
+    fn names_manifests_by_major_and_minor() {
+        assert_eq!(manifest_name("Release-2.4"), "2.4");
+        assert_eq!(manifest_name("Release-2.4.2"), "2.4");
+        assert_eq!(manifest_name("12.41"), "12.41");
+        assert_eq!(manifest_name("++Fortnite+Release-12.41-CL-12905909-Windows"), "12.41");
+    }
+
+    #[test]
+    fn names_unnumbered_manifests_by_version() {
+        assert_eq!(manifest_name("Cert-CL-3807424"), "Cert-CL-3807424");
+        assert_eq!(manifest_name("++Fortnite+Release-Cert-CL-3807424"), "Cert-CL-3807424");
+        assert_eq!(manifest_name("Live"), "Live");
+    }
+
+    #[test]
+    fn keeps_the_hotfix_in_the_version() {
+        let version = BuildVersion::parse("Release-2.4.2").unwrap();
+        assert_eq!(version.number, Some(ReleaseNumber { major: 2, minor: 4, patch: Some(2) }));
+        assert_eq!(version.to_string(), "Release-2.4.2");
+    }
+
+    #[test]
+    fn rejects_malformed_versions() {
+        assert!(BuildVersion::parse("").is_err());
+        assert!(BuildVersion::parse("Release").is_err());
+        assert!(BuildVersion::parse("Release-2.x").is_err());
+        assert!(BuildVersion::parse("Cert-CL-abc").is_err());
+    }
+}
@@ -0,0 +1,217 @@
+use reqwest::StatusCode;
+use serde::Serialize;
+use serde_json::{ Map, Value };
+use std::fmt;
+use std::io;
+
+use crate::builds::disk_space::InsufficientSpace;
+
+/// Stable reason a command failed. The frontend decides what to show, and whether a
+/// retry could help, from this rather than from the message text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The server couldn't be reached or the connection dropped.
+    Network,
+    Timeout,
+    /// The server answered with an unexpected status, found in `context.status`.
+    HttpStatus,
+    DiskFull,
+    Permission,
+    Cancelled,
+    NotFound,
+    /// Downloaded or on-disk data failed to decompress, parse or verify.
+    CorruptData,
+    /// A file's hash differs from the one it was expected to have. Deleting it and
+    /// downloading again is the fix.
+    ChecksumMismatch,
+    InvalidInput,
+    AlreadyRunning,
+    Unsupported,
+    Io,
+    Internal,
+}
+
+impl ErrorCode {
+    fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            Self::Network | Self::Timeout | Self::CorruptData | Self::ChecksumMismatch
+        )
+    }
+}
+
+/// Error returned by every Tauri command, serialized as
+/// `{ code, message, retryable, context }`.
+#[derive(Clone, Debug, Serialize)]
+pub struct LauncherError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub retryable: bool,
+    /// Extra machine-readable details such as the HTTP status, URL or byte counts.
+    pub context: Map<String, Value>,
+}
+
+impl LauncherError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            retryable: code.is_retryable(),
+            context: Map::new(),
+        }
+    }
+
+    pub fn with_context(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.context.insert(key.to_string(), value.into());
+        self
+    }
+
+    pub fn cancelled(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Cancelled, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidInput, message)
+    }
+
+    pub fn already_running(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::AlreadyRunning, message)
+    }
+
+    pub fn corrupt_data(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::CorruptData, message)
+    }
+
+    pub fn unsupported(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Unsupported, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+
+    /// A response with an unexpected status. Server errors and rate limiting are worth
+    /// retrying; other client errors are not.
+    pub fn http_status(status: StatusCode, url: &str, message: impl Into<String>) -> Self {
+        let mut error = Self::new(ErrorCode::HttpStatus, message)
+            .with_context("status", status.as_u16())
+            .with_context("url", url);
+        error.retryable = status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS;
+        error
+    }
+
+    /// Prefixes the message, keeping the code and context.
+    pub fn prefixed(mut self, prefix: impl fmt::Display) -> Self {
+        self.message = format!("{}: {}", prefix, self.message);
+        self
+    }
+}
+
+impl fmt::Display for LauncherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for LauncherError {}
+
+impl From<io::Error> for LauncherError {
+    fn from(error: io::Error) -> Self {
+        let code = if is_disk_full(&error) {
+            ErrorCode::DiskFull
+        } else {
+            match error.kind() {
+                io::ErrorKind::NotFound => ErrorCode::NotFound,
+                io::ErrorKind::PermissionDenied => ErrorCode::Permission,
+                io::ErrorKind::TimedOut => ErrorCode::Timeout,
+                _ => ErrorCode::Io,
+            }
+        };
+
+        let launcher_error = Self::new(code, error.to_string());
+        match error.raw_os_error() {
+            Some(os_error) => launcher_error.with_context("os_error", os_error),
+            None => launcher_error,
+        }
+    }
+}
+
+fn is_disk_full(error: &io::Error) -> bool {
+    if error.kind() == io::ErrorKind::StorageFull {
+        return true;
+    }
+
+    // ERROR_HANDLE_DISK_FULL, ERROR_DISK_FULL
+    #[cfg(windows)]
+    let disk_full_codes = [39, 112];
+    // ENOSPC, EDQUOT
+    #[cfg(not(windows))]
+    let disk_full_codes = [28, 122];
+
+    error.raw_os_error().is_some_and(|code| disk_full_codes.contains(&code))
+}
+
+impl From<reqwest::Error> for LauncherError {
+    fn from(error: reqwest::Error) -> Self {
+        let url = error
+            .url()
+            .map(|url| url.to_string())
+            .unwrap_or_default();
+
+        let launcher_error = if let Some(status) = error.status() {
+            Self::http_status(status, &url, error.to_string())
+        } else if error.is_timeout() {
+            Self::new(ErrorCode::Timeout, error.to_string())
+        } else if error.is_decode() {
+            Self::new(ErrorCode::CorruptData, error.to_string())
+        } else if error.is_builder() {
+            Self::new(ErrorCode::InvalidInput, error.to_string())
+        } else {
+            Self::new(ErrorCode::Network, error.to_string())
+        };
+
+        if url.is_empty() || launcher_error.context.contains_key("url") {
+            launcher_error
+        } else {
+            launcher_error.with_context("url", url)
+        }
+    }
+}
+
+impl From<serde_json::Error> for LauncherError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::corrupt_data(error.to_string())
+    }
+}
+
+impl From<tokio::task::JoinError> for LauncherError {
+    fn from(error: tokio::task::JoinError) -> Self {
+        Self::internal(format!("Background task failed: {}", error))
+    }
+}
+
+impl From<InsufficientSpace> for LauncherError {
+    fn from(error: InsufficientSpace) -> Self {
+        Self::new(ErrorCode::DiskFull, error.to_string())
+            .with_context("path", error.path.clone())
+            .with_context("required_bytes", error.required_bytes)
+            .with_context("available_bytes", error.available_bytes)
+    }
+}
+
+/// `.context("Failed to ...")` on any result whose error converts into a
+/// `LauncherError`, producing "Failed to ...: <original message>".
+pub trait ResultExt<T> {
+    fn context(self, prefix: impl fmt::Display) -> Result<T, LauncherError>;
+}
+
+impl<T, E: Into<LauncherError>> ResultExt<T> for Result<T, E> {
+    fn context(self, prefix: impl fmt::Display) -> Result<T, LauncherError> {
+        self.map_err(|error| error.into().prefixed(prefix))
+    }
+}
@@ -1,20 +1,16 @@
 use declarative_discord_rich_presence::DeclarativeDiscordIpcClient;
 use declarative_discord_rich_presence::activity::{ Activity, Assets, Button, Timestamps };
-use futures_util::StreamExt;
 use regex::Regex;
-use reqwest::StatusCode;
 use std::ffi::CString;
-use std::fs::{ self, File, OpenOptions };
+use std::fs::{ self, File };
 use std::io::Read;
 use std::io::Write;
 use std::os::windows::process::CommandExt;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Stdio;
-use std::time::{ Duration, Instant };
+use std::time::Duration;
 use sysinfo::{ System, SystemExt };
-use tauri::AppHandle;
-use tauri::Emitter;
 use tauri::Manager;
 use tauri::WindowEvent;
 use winapi::um::winbase::CREATE_SUSPENDED;
@@ -24,25 +20,45 @@ use windows::Win32::UI::WindowsAndMessaging::{ SW_HIDE, SW_SHOW };
 use windows::core::PCSTR;
 
 mod builds;
+mod error;
+use error::{ ErrorCode, LauncherError, ResultExt };
 use builds::download_manager::{
     DownloadManager,
     cancel_download,
     cancel_extraction,
+    discard_resumable_install,
     download_build,
+    download_game_file,
     get_available_versions,
     get_default_install_dir,
+    get_download_queue,
+    get_download_rate_limit,
     get_manifest_for_version,
     is_download_active,
+    is_download_paused,
     is_extraction_active,
+    list_downloads,
+    list_resumable_installs,
+    move_download_in_queue,
+    pause_download,
+    prioritize_download,
+    resume_download,
+    set_download_rate_limit,
+    set_max_concurrent_downloads,
 };
+use builds::catalogue::get_version_catalogue;
+use builds::checksum::ExpectedHash;
+use builds::chunk_cache::{ clear_chunk_cache, get_chunk_cache_info, set_chunk_cache_limit };
+use builds::disk_space::check_disk_space;
+use builds::mirrors::{ check_mirrors, get_mirrors, set_mirrors };
+use builds::network::{ self, get_network_settings, set_network_settings };
+use builds::verify::{ repair_build, verify_build };
+use builds::version::parse_build_version;
 
 const CREATE_NO_WINDOW: u32 = 0x08000000;
-const MAX_RETRIES: usize = 300;
-const TIMEOUT_SECONDS: u64 = 300;
-const MIN_PROGRESS_INTERVAL_MS: u64 = 500;
 
 #[tauri::command]
-fn get_fortnite_processid() -> Result<Option<String>, String> {
+fn get_fortnite_processid() -> Result<Option<String>, LauncherError> {
     let output = std::process::Command
         ::new("wmic")
         .creation_flags(CREATE_NO_WINDOW)
@@ -55,8 +71,7 @@ fn get_fortnite_processid() -> Result<Option<String>, String> {
                 "ExecutablePath",
             ]
         )
-        .output()
-        .map_err(|e| e.to_string())?;
+        .output()?;
 
     let output_str = String::from_utf8_lossy(&output.stdout);
 
@@ -71,7 +86,7 @@ fn get_fortnite_processid() -> Result<Option<String>, String> {
 }
 
 #[tauri::command]
-fn exit_all() -> Result<(), String> {
+fn exit_all() -> Result<(), LauncherError> {
     use std::env;
     use std::fs::File;
     use std::io::Write;
@@ -92,22 +107,24 @@ fn exit_all() -> Result<(), String> {
     let temp_dir = env::temp_dir();
     let batch_path = temp_dir.join("close.bat");
 
-    let mut batch_file = File::create(&batch_path).map_err(|e|
-        format!("Failed to create batch file: {}", e)
-    )?;
+    let mut batch_file = File::create(&batch_path).context("Failed to create batch file")?;
 
-    writeln!(batch_file, "@echo off").map_err(|e| format!("Write error: {}", e))?;
+    writeln!(batch_file, "@echo off").context("Write error")?;
     for process in processes {
-        writeln!(batch_file, "taskkill /F /IM \"{}\" >nul 2>&1", process).map_err(|e|
-            format!("Write error: {}", e)
+        writeln!(batch_file, "taskkill /F /IM \"{}\" >nul 2>&1", process).context(
+            "Write error"
         )?;
     }
-    writeln!(batch_file, "del \"%~f0\"").map_err(|e| format!("Write error: {}", e))?;
+    writeln!(batch_file, "del \"%~f0\"").context("Write error")?;
 
     drop(batch_file);
 
-    let batch_path_str = batch_path.to_str().ok_or("Invalid path")?;
-    let batch_cstring = CString::new(batch_path_str).map_err(|e| format!("CString error: {}", e))?;
+    let batch_path_str = batch_path
+        .to_str()
+        .ok_or_else(|| LauncherError::invalid_input("Invalid path"))?;
+    let batch_cstring = CString::new(batch_path_str).map_err(|e| {
+        LauncherError::invalid_input(format!("CString error: {}", e))
+    })?;
 
     let result = unsafe {
         ShellExecuteA(
@@ -121,36 +138,47 @@ fn exit_all() -> Result<(), String> {
     };
 
     if result.is_invalid() {
-        return Err("Failed to close game with batch file".to_string());
+        return Err(
+            LauncherError::new(ErrorCode::Permission, "Failed to close game with batch file")
+        );
     }
 
     Ok(())
 }
 
+/// Whether `path` exists with the given size and, when `hash` is given, contents. A
+/// file failing either check needs downloading again.
 #[tauri::command]
-async fn check_file_exists_and_size(path: &str, size: Option<u64>) -> Result<bool, String> {
+async fn check_file_exists_and_size(
+    path: &str,
+    size: Option<u64>,
+    hash: Option<ExpectedHash>
+) -> Result<bool, LauncherError> {
     let file_path = std::path::PathBuf::from(path);
     if !file_path.exists() {
         return Ok(false);
     }
 
-    match size {
-        Some(expected_size) => {
-            let actual_size = match file_path.metadata() {
-                Ok(metadata) => metadata.len(),
-                Err(err) => {
-                    return Err(err.to_string());
-                }
-            };
+    if let Some(expected_size) = size {
+        if file_path.metadata()?.len() != expected_size {
+            return Ok(false);
+        }
+    }
 
-            Ok(actual_size == expected_size)
+    match hash {
+        Some(hash) => {
+            match hash.verify(&file_path).await {
+                Ok(()) => Ok(true),
+                Err(e) if e.code == ErrorCode::ChecksumMismatch => Ok(false),
+                Err(e) => Err(e),
+            }
         }
         None => Ok(true),
     }
 }
 
 #[tauri::command]
-async fn check_file_exists(path: &str) -> Result<bool, String> {
+async fn check_file_exists(path: &str) -> Result<bool, LauncherError> {
     let file_path = std::path::PathBuf::from(path);
 
     if !file_path.exists() {
@@ -161,10 +189,10 @@ async fn check_file_exists(path: &str) -> Result<bool, String> {
 }
 
 #[tauri::command]
-fn search_for_version(path: &str) -> Result<Vec<String>, String> {
-    let mut file = File::open(path).map_err(|e| e.to_string())?;
+fn search_for_version(path: &str) -> Result<Vec<String>, LauncherError> {
+    let mut file = File::open(path)?;
     let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer).map_err(|e| e.to_string())?;
+    file.read_to_end(&mut buffer)?;
 
     let pattern = [
         0x2b, 0x00, 0x2b, 0x00, 0x46, 0x00, 0x6f, 0x00, 0x72, 0x00, 0x74, 0x00, 0x6e, 0x00, 0x69, 0x00,
@@ -261,11 +289,11 @@ fn exit() -> Result<(), String> {
     Ok(())
 }
 
-fn download_file(url: &str, dest: &Path) -> Result<(), Box<dyn std::error::Error>> {
+fn download_file(url: &str, dest: &Path) -> Result<(), LauncherError> {
     if let Some(parent) = dest.parent() {
         fs::create_dir_all(parent)?;
     }
-    let response = reqwest::blocking::get(url)?;
+    let response = network::blocking_client()?.get(url).send()?;
     let mut file = fs::File::create(dest)?;
     let content = response.bytes()?;
     file.write_all(&content)?;
@@ -273,14 +301,14 @@ fn download_file(url: &str, dest: &Path) -> Result<(), Box<dyn std::error::Error
 }
 
 #[tauri::command]
-fn get_user_ip() -> Result<String, String> {
-    match reqwest::blocking::get("https://api4.ipify.org") {
+fn get_user_ip() -> Result<String, LauncherError> {
+    match network::blocking_client()?.get("https://api4.ipify.org").send() {
         Ok(response) =>
             match response.text() {
                 Ok(ip) => Ok(ip),
-                Err(e) => Err(format!("Failed to parse response: {}", e)),
+                Err(e) => Err(LauncherError::from(e).prefixed("Failed to parse response")),
             }
-        Err(e) => Err(format!("Failed to make request: {}", e)),
+        Err(e) => Err(LauncherError::from(e).prefixed("Failed to make request")),
     }
 }
 
@@ -294,7 +322,7 @@ fn experience(
     ror: bool,
     a: String,
     version: String
-) -> Result<bool, String> {
+) -> Result<bool, LauncherError> {
     std::thread::sleep(std::time::Duration::from_secs(2));
     let game_path = PathBuf::from(folder_path);
 
@@ -320,9 +348,9 @@ fn experience(
         if let Some(parent_dir) = game_dll.parent() {
             if !parent_dir.exists() {
                 println!("Creating directory: {:?}", parent_dir);
-                if let Err(e) = std::fs::create_dir_all(parent_dir) {
-                    return Err(format!("Failed to create directory {:?}: {}", parent_dir, e));
-                }
+                std::fs
+                    ::create_dir_all(parent_dir)
+                    .context(format!("Failed to create directory {:?}", parent_dir))?;
             }
         }
 
@@ -337,16 +365,18 @@ fn experience(
                         if metadata.len() > 0 {
                             println!("DLL file size: {} bytes", metadata.len());
                         } else {
-                            return Err("Downloaded DLL file is empty".to_string());
+                            return Err(LauncherError::corrupt_data("Downloaded DLL file is empty"));
                         }
                     }
                     Err(e) => {
-                        return Err(format!("Failed to verify downloaded DLL: {}", e));
+                        return Err(
+                            LauncherError::from(e).prefixed("Failed to verify downloaded DLL")
+                        );
                     }
                 }
             }
             Err(e) => {
-                return Err(format!("Failed to download DLL: {}", e));
+                return Err(e.prefixed("Failed to download DLL"));
             }
         }
 
@@ -456,12 +486,14 @@ fn experience(
     }
 
     let hwnd: HWND = HWND(std::ptr::null_mut());
-    let args_cstring = CString::new(fort_args.join(" ")).map_err(|e|
-        format!("CString error: {}", e)
-    )?;
+    let args_cstring = CString::new(fort_args.join(" ")).map_err(|e| {
+        LauncherError::invalid_input(format!("CString error: {}", e))
+    })?;
 
-    let exe_str = game_real.to_str().ok_or("Invalid path")?;
-    let exe_cstring = CString::new(exe_str).map_err(|e| format!("CString error: {}", e))?;
+    let exe_str = game_real.to_str().ok_or_else(|| LauncherError::invalid_input("Invalid path"))?;
+    let exe_cstring = CString::new(exe_str).map_err(|e| {
+        LauncherError::invalid_input(format!("CString error: {}", e))
+    })?;
 
     let result = unsafe {
         ShellExecuteA(
@@ -475,7 +507,7 @@ fn experience(
     };
 
     if result.is_invalid() {
-        return Err("Failed to start Solaris".to_string());
+        return Err(LauncherError::new(ErrorCode::Permission, "Failed to start Solaris"));
     }
 
     std::thread::sleep(std::time::Duration::from_secs(5));
@@ -522,239 +554,36 @@ fn rich_presence(username: String, character: String) {
 }
 
 #[tauri::command]
-async fn check_game_exists(path: &str) -> Result<bool, String> {
+async fn check_game_exists(path: &str) -> Result<bool, LauncherError> {
     let game_path = PathBuf::from(path);
     let mut game = game_path.clone();
     game.push("FortniteGame\\Binaries\\Win64\\FortniteClient-Win64-Shipping.exe");
 
     if !game.exists() {
-        return Err("Hmmm could not find all Fortnite files".to_string());
+        return Err(
+            LauncherError::not_found("Hmmm could not find all Fortnite files").with_context(
+                "path",
+                game.to_string_lossy().to_string()
+            )
+        );
     } else {
         Ok(true)
     }
 }
 
 #[tauri::command]
-async fn download_game_file(url: &str, dest: &str, app: AppHandle) -> Result<(), String> {
-    let dest_path = Path::new(dest);
-
-    if let Some(parent) = dest_path.parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directories: {}", e))?;
-    }
-
-    let filename = dest_path
-        .file_name()
-        .and_then(|f| f.to_str())
-        .unwrap_or("unknown");
-
-    let mut downloaded: u64 = 0;
-    let mut retry_count: usize = 0;
-    let mut file_size: u64 = 0;
-
-    if dest_path.exists() {
-        match fs::remove_file(dest_path) {
-            Ok(_) => {
-                downloaded = 0;
-
-                let _ = app.emit(
-                    "download-progress",
-                    serde_json::json!({
-                        "filename": filename,
-                        "downloaded": downloaded,
-                        "total": 0,
-                        "progress": 0,
-                        "speed": 0.0,
-                        "message": "Old file deleted, starting fresh download..."
-                    })
-                );
-            }
-            Err(e) => {
-                return Err(format!("Failed to delete old file: {}", e));
-            }
-        }
-    }
-
-    let mut last_update_time = Instant::now();
-    let mut bytes_since_last_update: u64 = 0;
-    let mut last_progress_percentage: u64 = 0;
-
-    let client = reqwest::Client
-        ::builder()
-        .timeout(Duration::from_secs(TIMEOUT_SECONDS))
-        .connect_timeout(Duration::from_secs(30))
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-
-    while retry_count < MAX_RETRIES {
-        if retry_count > 0 {
-            let _ = app.emit(
-                "download-progress",
-                serde_json::json!({
-                    "filename": filename,
-                    "downloaded": downloaded,
-                    "total": file_size,
-                    "progress": if file_size > 0 { (downloaded * 100) / file_size } else { 0 },
-                    "speed": 0.0,
-                    "message": format!("Retry attempt {} of {}", retry_count, MAX_RETRIES)
-                })
-            );
-        }
-
-        let mut file = if downloaded > 0 {
-            match OpenOptions::new().write(true).append(true).open(dest_path) {
-                Ok(f) => f,
-                Err(_e) => {
-                    downloaded = 0;
-                    File::create(dest_path).map_err(|e| format!("Failed to create file: {}", e))?
-                }
-            }
-        } else {
-            File::create(dest_path).map_err(|e| format!("Failed to create file: {}", e))?
-        };
-
-        let mut request = client.get(url);
-        if downloaded > 0 {
-            request = request.header("Range", format!("bytes={}-", downloaded));
-        }
-
-        match request.send().await {
-            Ok(response) => {
-                let status = response.status();
-
-                if !(status.is_success() || status == StatusCode::PARTIAL_CONTENT) {
-                    retry_count += 1;
-                    continue;
-                }
-
-                if file_size == 0 {
-                    file_size = if status == StatusCode::PARTIAL_CONTENT {
-                        if let Some(content_range) = response.headers().get("content-range") {
-                            if let Ok(range_str) = content_range.to_str() {
-                                if let Some(size_str) = range_str.split('/').nth(1) {
-                                    size_str.parse::<u64>().unwrap_or(0)
-                                } else {
-                                    0
-                                }
-                            } else {
-                                0
-                            }
-                        } else {
-                            response.content_length().unwrap_or(0) + downloaded
-                        }
-                    } else {
-                        response.content_length().unwrap_or(0)
-                    };
-                }
-
-                let mut stream = response.bytes_stream();
-                let mut chunk_timeout = false;
-
-                while let Some(chunk_result) = stream.next().await {
-                    match chunk_result {
-                        Ok(chunk) => {
-                            chunk_timeout = false;
-
-                            match file.write_all(&chunk) {
-                                Ok(_) => {
-                                    downloaded += chunk.len() as u64;
-                                    bytes_since_last_update += chunk.len() as u64;
-
-                                    let progress = if file_size > 0 {
-                                        (downloaded * 100) / file_size
-                                    } else {
-                                        0
-                                    };
-
-                                    let time_since_update = last_update_time.elapsed();
-                                    if
-                                        progress > last_progress_percentage ||
-                                        time_since_update.as_millis() >
-                                            (MIN_PROGRESS_INTERVAL_MS as u128)
-                                    {
-                                        let speed_mbps = if time_since_update.as_secs_f64() > 0.0 {
-                                            (bytes_since_last_update as f64) /
-                                                (1024.0 * 1024.0) /
-                                                time_since_update.as_secs_f64()
-                                        } else {
-                                            0.0
-                                        };
-
-                                        let _ = app.emit(
-                                            "download-progress",
-                                            serde_json::json!({
-                                                "filename": filename,
-                                                "downloaded": downloaded,
-                                                "total": file_size,
-                                                "progress": progress,
-                                                "speed": speed_mbps,
-                                                "message": "Downloading..."
-                                            })
-                                        );
-
-                                        last_progress_percentage = progress;
-                                        last_update_time = Instant::now();
-                                        bytes_since_last_update = 0;
-                                    }
-                                }
-                                Err(e) => {
-                                    return Err(format!("Failed to write to file: {}", e));
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            if e.is_timeout() {
-                                chunk_timeout = true;
-                                break;
-                            }
-                            return Err(format!("Error downloading: {}", e));
-                        }
-                    }
-                }
-
-                if !chunk_timeout {
-                    if file_size == 0 || downloaded >= file_size {
-                        let _ = app.emit(
-                            "download-completed",
-                            serde_json::json!({
-                                "filename": filename,
-                                "size": downloaded
-                            })
-                        );
-                        return Ok(());
-                    }
-                }
-
-                retry_count += 1;
-            }
-            Err(e) => {
-                if e.is_timeout() {
-                    retry_count += 1;
-                    continue;
-                }
-                return Err(format!("Request error: {}", e));
-            }
-        }
-    }
-
-    Err(format!("Failed to download after {} retries", MAX_RETRIES))
-}
-
-#[tauri::command]
-fn delete_file(file_path: String) -> Result<(), String> {
+fn delete_file(file_path: String) -> Result<(), LauncherError> {
     let path = Path::new(&file_path);
 
     if !path.exists() {
-        return Err(format!("File does not exist: {}", file_path));
+        return Err(LauncherError::not_found(format!("File does not exist: {}", file_path)));
     }
 
     if !path.is_file() {
-        return Err(format!("{} is not a file", file_path));
+        return Err(LauncherError::invalid_input(format!("{} is not a file", file_path)));
     }
 
-    match fs::remove_file(path) {
-        Ok(_) => Ok(()),
-        Err(e) => Err(format!("Failed to delete file: {}", e)),
-    }
+    fs::remove_file(path).context("Failed to delete file")
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -824,11 +653,36 @@ pub fn run() {
                 is_download_active,
                 is_extraction_active,
                 cancel_download,
+                pause_download,
+                resume_download,
+                is_download_paused,
+                list_downloads,
+                get_download_queue,
+                set_max_concurrent_downloads,
+                move_download_in_queue,
+                prioritize_download,
+                set_download_rate_limit,
+                get_download_rate_limit,
                 cancel_extraction,
                 delete_file,
                 get_default_install_dir,
                 get_available_versions,
                 get_manifest_for_version,
+                get_version_catalogue,
+                parse_build_version,
+                list_resumable_installs,
+                discard_resumable_install,
+                verify_build,
+                repair_build,
+                get_mirrors,
+                set_mirrors,
+                check_mirrors,
+                get_network_settings,
+                set_network_settings,
+                get_chunk_cache_info,
+                clear_chunk_cache,
+                set_chunk_cache_limit,
+                check_disk_space,
                 get_user_ip
             ]
         )
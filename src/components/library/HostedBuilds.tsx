@@ -7,6 +7,7 @@ import useBuildStore from "@/modules/zustand/library/useBuilds";
 import {
   cancelDownload,
   downloadBuild,
+  ExtractionProgress,
   getAvailableVersions,
   getDefaultInstallDir,
   getManifestForVersion,
@@ -56,7 +57,7 @@ export default function HostedBuilds({ isOpen, onClose }: HostedBuildsProps) {
         if (!isActive) resetDownload();
       }
 
-      const newManifestBuilds = versions.map((version) => ({
+      const newManifestBuilds = versions.data.map((version) => ({
         id: version,
         title: `Fortnite ${version}`,
         version,
@@ -64,7 +65,7 @@ export default function HostedBuilds({ isOpen, onClose }: HostedBuildsProps) {
         size: "Unknown",
         imageUrl:
           "https://external-content.duckduckgo.com/iu/?u=https%3A%2F%2Fi.pinimg.com%2Foriginals%2F56%2F99%2F76%2F569976a6a9b483d9bcf4da782552f7d1.jpg&f=1&nofb=1&ipt=d7a982bc35824a9ab8f7b283cef1a2699125df6c8e968218d8c8b5e3f71e6065&ipo=images",
-        tags: ["Manifest"],
+        tags: versions.stale ? ["Manifest", "Offline"] : ["Manifest"],
         useManifest: true,
       }));
 
@@ -89,7 +90,7 @@ export default function HostedBuilds({ isOpen, onClose }: HostedBuildsProps) {
 
     if (build.useManifest && build.version) {
       try {
-        const manifest = await getManifestForVersion(build.version);
+        const { data: manifest } = await getManifestForVersion(build.version);
         const sizeInBytes = manifest.size;
         const sizeInGB = (sizeInBytes / (1024 * 1024 * 1024)).toFixed(2);
 
@@ -158,7 +159,7 @@ export default function HostedBuilds({ isOpen, onClose }: HostedBuildsProps) {
       console.log(installDir);
       await downloadBuild(
         build.id,
-        "",
+        build.url ?? "",
         installDir,
         (progress: DownloadProgressType) => {
           useDownloadStore.getState().setDownloadState({
@@ -170,7 +171,14 @@ export default function HostedBuilds({ isOpen, onClose }: HostedBuildsProps) {
             eta: progress.eta,
           });
         },
-        undefined,
+        (progress: ExtractionProgress) => {
+          useDownloadStore.getState().setDownloadState({
+            ...useDownloadStore.getState().downloadState,
+            status: "extracting",
+            progress: progress.percentage,
+            eta: progress.eta,
+          });
+        },
         async () => {
           const escapedPath = installDir.replace(/\\/g, "\\\\");
           const shippingPath = `${escapedPath}\\${build.id}\\FortniteGame\\Binaries\\Win64\\FortniteClient-Win64-Shipping.exe`;
@@ -181,7 +189,11 @@ export default function HostedBuilds({ isOpen, onClose }: HostedBuildsProps) {
         handleDownloadError,
         undefined,
         build.useManifest,
-        build.version
+        build.version,
+        undefined,
+        undefined,
+        !build.useManifest,
+        !build.useManifest
       );
     } catch (error) {
       console.error("Failed to start download:", error);
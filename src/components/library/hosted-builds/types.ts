@@ -7,6 +7,8 @@ export interface HostedBuild {
   imageUrl: string;
   tags: string[];
   useManifest?: boolean;
+  /** Direct download URL, for builds that aren't installed from a manifest. */
+  url?: string;
 }
 
 export interface HostedBuildsProps {
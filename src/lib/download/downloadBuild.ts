@@ -2,6 +2,7 @@ import { appDataDir } from "@tauri-apps/api/path";
 import { listen } from "@tauri-apps/api/event";
 import { invoke } from "@tauri-apps/api/core";
 import { open } from "@tauri-apps/plugin-dialog";
+import { errorMessage } from "@/lib/errors";
 
 export interface DownloadProgress {
   percentage: number;
@@ -9,6 +10,7 @@ export interface DownloadProgress {
   downloaded: string;
   total: string;
   eta: string;
+  rateLimit: string | null;
 }
 
 export interface ExtractionProgress {
@@ -25,6 +27,100 @@ export interface ManifestFile {
   size: number;
 }
 
+export interface Cached<T> {
+  data: T;
+  stale: boolean;
+  fetched_at: number;
+}
+
+export interface CatalogueEntry {
+  version: string;
+  release: string | null;
+  changelist: number | null;
+  chapter: number | null;
+  season: number | null;
+  total_size: number | null;
+  file_count: number | null;
+  manifest_available: boolean;
+  release_date: string | null;
+}
+
+export interface CatalogueQuery {
+  chapter?: number;
+  season?: number;
+  available_only?: boolean;
+  sort?: "version" | "changelist" | "size" | "release_date";
+  descending?: boolean;
+}
+
+export interface BuildVersion {
+  branch: "release" | "cert" | "live" | { other: string };
+  number: { major: number; minor: number; patch: number | null } | null;
+  changelist: number | null;
+}
+
+export interface MirrorList {
+  manifest_mirrors: string[];
+  version_mirrors: string[];
+  epic_chunk_base_url: string | null;
+}
+
+export interface EpicManifestSource {
+  /** URL or local path of the binary manifest. */
+  manifest: string;
+  chunkBaseUrl?: string;
+}
+
+export interface MirrorHealth {
+  url: string;
+  kind: "manifest" | "version";
+  reachable: boolean;
+  status: number | null;
+  latency_ms: number | null;
+  error: string | null;
+}
+
+export interface NetworkSettings {
+  proxy: string | null;
+  no_proxy: string[];
+  ca_certificates: string[];
+}
+
+export interface ChunkCacheInfo {
+  path: string;
+  entries: number;
+  total_bytes: number;
+  max_bytes: number;
+}
+
+export interface DiskSpaceReport {
+  path: string;
+  mount_point: string | null;
+  required_bytes: number;
+  temp_overhead_bytes: number;
+  available_bytes: number | null;
+  sufficient: boolean;
+}
+
+export type DownloadState =
+  | { state: "queued"; position: number | null }
+  | { state: "downloading" }
+  | { state: "paused" }
+  | { state: "verifying" }
+  | { state: "extracting" }
+  | { state: "completed" }
+  | { state: "failed"; reason: string };
+
+export type DownloadSnapshot = DownloadState & {
+  build_id: string;
+  progress: TauriDownloadProgress | null;
+  step_percentage: number | null;
+  created_at: number;
+  started_at: number | null;
+  updated_at: number;
+  finished_at: number | null;
+};
+
 export type DownloadProgressCallback = (progress: DownloadProgress) => void;
 export type ExtractionProgressCallback = (progress: ExtractionProgress) => void;
 export type DownloadCompleteCallback = () => void;
@@ -32,13 +128,31 @@ export type ExtractionCompleteCallback = () => void;
 export type DownloadErrorCallback = (error: string) => void;
 export type ExtractionErrorCallback = (error: string) => void;
 
-interface TauriDownloadProgress {
+interface TauriExtractionProgress {
+  build_id: string;
+  percentage: number;
+  current_file: string;
+  total_files: number;
+  processed_files: number;
+  eta: string;
+}
+
+/** File extension of the archive at `url`, keeping `.tar.gz` whole. */
+function archiveExtension(url: string): string {
+  const name = url.split(/[?#]/)[0].split("/").pop() ?? "";
+  if (name.toLowerCase().endsWith(".tar.gz")) return "tar.gz";
+  return name.includes(".") ? name.split(".").pop()! : "";
+}
+
+export interface TauriDownloadProgress {
   build_id: string;
   percentage: number;
   downloaded_bytes: number;
   total_bytes: number;
   speed: number;
   eta: string;
+  paused: boolean;
+  rate_limit: number | null;
 }
 
 function formatBytes(bytes: number): string {
@@ -96,18 +210,34 @@ export async function selectInstallDirectory(defaultPath?: string): Promise<stri
   }
 }
 
-export async function getAvailableVersions(): Promise<string[]> {
+export async function getAvailableVersions(): Promise<Cached<string[]>> {
   try {
-    return await invoke<string[]>("get_available_versions");
+    return await invoke<Cached<string[]>>("get_available_versions");
   } catch (error) {
     console.error("Error fetching available versions:", error);
-    return [];
+    return { data: [], stale: false, fetched_at: 0 };
   }
 }
 
-export async function getManifestForVersion(version: string): Promise<ManifestFile> {
+export async function getVersionCatalogue(
+  query: CatalogueQuery = {}
+): Promise<Cached<CatalogueEntry[]> | null> {
   try {
-    return await invoke<ManifestFile>("get_manifest_for_version", { version });
+    return await invoke<Cached<CatalogueEntry[]>>("get_version_catalogue", { query });
+  } catch (error) {
+    console.error("Error fetching version catalogue:", error);
+    return null;
+  }
+}
+
+/** Rejects with an `invalid_input` LauncherError when the version isn't recognised. */
+export async function parseBuildVersion(version: string): Promise<BuildVersion> {
+  return await invoke<BuildVersion>("parse_build_version", { version });
+}
+
+export async function getManifestForVersion(version: string): Promise<Cached<ManifestFile>> {
+  try {
+    return await invoke<Cached<ManifestFile>>("get_manifest_for_version", { version });
   } catch (error) {
     console.error("Error fetching manifest:", error);
     throw error;
@@ -125,17 +255,24 @@ export async function downloadBuild(
   onError?: DownloadErrorCallback,
   onExtractionError?: ExtractionErrorCallback,
   useManifest?: boolean,
-  version?: string
+  version?: string,
+  epicManifest?: EpicManifestSource,
+  checksum?: string,
+  extract?: boolean,
+  deleteAfterExtract?: boolean
 ): Promise<boolean> {
+  useManifest = useManifest || epicManifest !== undefined;
   try {
     const isActive = await invoke<boolean>("is_download_active", { buildId });
     if (isActive) {
       if (onError) onError("Download already in progress");
       return false;
     }
-    const destination = useManifest
-      ? `${installDir}/${buildId}`
-      : `${installDir}/${buildId}.${url.split(".").pop()}`;
+    const extension = archiveExtension(url);
+    const destination =
+      useManifest || !extension
+        ? `${installDir}/${buildId}`
+        : `${installDir}/${buildId}.${extension}`;
 
     const unlistenDownloadProgress = await listen<TauriDownloadProgress>(
       "download:progress",
@@ -147,6 +284,8 @@ export async function downloadBuild(
             downloaded: formatBytes(event.payload.downloaded_bytes),
             total: formatBytes(event.payload.total_bytes),
             eta: event.payload.eta,
+            rateLimit:
+              event.payload.rate_limit === null ? null : formatSpeed(event.payload.rate_limit),
           });
         }
       }
@@ -171,16 +310,40 @@ export async function downloadBuild(
       }
     });
 
+    const unlistenExtractionProgress = await listen<TauriExtractionProgress>(
+      "extraction:progress",
+      (event) => {
+        if (event.payload.build_id === buildId && onExtractionProgress) {
+          onExtractionProgress({
+            percentage: Math.round(event.payload.percentage),
+            currentFile: event.payload.current_file,
+            totalFiles: event.payload.total_files,
+            processedFiles: event.payload.processed_files,
+            eta: event.payload.eta,
+          });
+        }
+      }
+    );
+
+    const unlistenExtractionError = await listen<string>("extraction:failed", (event) => {
+      if (event.payload === buildId && onExtractionError) {
+        onExtractionError("Extraction failed");
+      }
+    });
+
     try {
       await invoke("download_build", {
         request: {
           build_id: buildId,
           url,
           destination,
-          extract: false,
-          delete_after_extract: false,
+          extract: !useManifest && (extract ?? false),
+          delete_after_extract: deleteAfterExtract ?? false,
           use_manifest: useManifest,
           version: version,
+          epic_manifest: epicManifest?.manifest ?? null,
+          chunk_base_url: epicManifest?.chunkBaseUrl ?? null,
+          checksum: checksum ?? null,
         },
       });
     } catch (error) {
@@ -188,13 +351,21 @@ export async function downloadBuild(
       unlistenDownloadComplete();
       unlistenDownloadError();
 
-      if (onError) onError(error instanceof Error ? error.message : String(error));
+      if (onError) onError(errorMessage(error));
       return false;
+    } finally {
+      unlistenExtractionProgress();
+      unlistenExtractionError();
+    }
+
+    // Extraction, when there was any, has finished by the time the command returns.
+    if (!useManifest && onExtractionComplete) {
+      onExtractionComplete();
     }
 
     return true;
   } catch (error) {
-    if (onError) onError(error instanceof Error ? error.message : String(error));
+    if (onError) onError(errorMessage(error));
     return false;
   }
 }
@@ -208,6 +379,24 @@ export async function cancelDownload(buildId: string): Promise<boolean> {
   }
 }
 
+export async function pauseDownload(buildId: string): Promise<boolean> {
+  try {
+    return await invoke<boolean>("pause_download", { buildId });
+  } catch (error) {
+    console.error("Error pausing download:", error);
+    return false;
+  }
+}
+
+export async function resumeDownload(buildId: string): Promise<boolean> {
+  try {
+    return await invoke<boolean>("resume_download", { buildId });
+  } catch (error) {
+    console.error("Error resuming download:", error);
+    return false;
+  }
+}
+
 export async function cancelExtraction(buildId: string): Promise<boolean> {
   try {
     return await invoke<boolean>("cancel_extraction", { buildId });
@@ -216,3 +405,129 @@ export async function cancelExtraction(buildId: string): Promise<boolean> {
     return false;
   }
 }
+
+/**
+ * Limits one download when `buildId` is given, otherwise all of them together.
+ * Game files are identified as `file:<destination path>`.
+ */
+export async function setDownloadRateLimit(
+  bytesPerSec: number | null,
+  buildId?: string
+): Promise<boolean> {
+  try {
+    await invoke("set_download_rate_limit", { buildId: buildId ?? null, bytesPerSec });
+    return true;
+  } catch (error) {
+    console.error("Error setting download rate limit:", error);
+    return false;
+  }
+}
+
+export async function getDownloadRateLimit(buildId?: string): Promise<number | null> {
+  try {
+    return await invoke<number | null>("get_download_rate_limit", { buildId: buildId ?? null });
+  } catch (error) {
+    console.error("Error getting download rate limit:", error);
+    return null;
+  }
+}
+
+export async function getMirrors(): Promise<MirrorList | null> {
+  try {
+    return await invoke<MirrorList>("get_mirrors");
+  } catch (error) {
+    console.error("Error getting mirrors:", error);
+    return null;
+  }
+}
+
+export async function setMirrors(
+  manifestMirrors: string[],
+  versionMirrors: string[],
+  epicChunkBaseUrl: string | null = null
+): Promise<MirrorList> {
+  return await invoke<MirrorList>("set_mirrors", {
+    manifestMirrors,
+    versionMirrors,
+    epicChunkBaseUrl,
+  });
+}
+
+export async function checkMirrors(version?: string): Promise<MirrorHealth[]> {
+  try {
+    return await invoke<MirrorHealth[]>("check_mirrors", { version: version ?? null });
+  } catch (error) {
+    console.error("Error checking mirrors:", error);
+    return [];
+  }
+}
+
+export async function getNetworkSettings(): Promise<NetworkSettings | null> {
+  try {
+    return await invoke<NetworkSettings>("get_network_settings");
+  } catch (error) {
+    console.error("Error getting network settings:", error);
+    return null;
+  }
+}
+
+export async function setNetworkSettings(
+  proxy: string | null,
+  noProxy: string[],
+  caCertificates: string[]
+): Promise<NetworkSettings> {
+  return await invoke<NetworkSettings>("set_network_settings", {
+    proxy,
+    noProxy,
+    caCertificates,
+  });
+}
+
+export async function getChunkCacheInfo(): Promise<ChunkCacheInfo | null> {
+  try {
+    return await invoke<ChunkCacheInfo>("get_chunk_cache_info");
+  } catch (error) {
+    console.error("Error getting chunk cache info:", error);
+    return null;
+  }
+}
+
+export async function clearChunkCache(): Promise<number> {
+  try {
+    return await invoke<number>("clear_chunk_cache");
+  } catch (error) {
+    console.error("Error clearing chunk cache:", error);
+    return 0;
+  }
+}
+
+export async function setChunkCacheLimit(maxBytes: number): Promise<boolean> {
+  try {
+    await invoke<number>("set_chunk_cache_limit", { maxBytes });
+    return true;
+  } catch (error) {
+    console.error("Error setting chunk cache limit:", error);
+    return false;
+  }
+}
+
+export async function checkDiskSpace(
+  destination: string,
+  options: { version?: string; url?: string; extract?: boolean }
+): Promise<DiskSpaceReport> {
+  return await invoke<DiskSpaceReport>("check_disk_space", {
+    destination,
+    version: options.version ?? null,
+    url: options.url ?? null,
+    extract: options.extract ?? null,
+  });
+}
+
+export async function listDownloads(): Promise<DownloadSnapshot[]> {
+  try {
+    return await invoke<DownloadSnapshot[]>("list_downloads");
+  } catch (error) {
+    console.error("Error listing downloads:", error);
+    return [];
+  }
+}
@@ -0,0 +1,41 @@
+export type LauncherErrorCode =
+  | "network"
+  | "timeout"
+  | "http_status"
+  | "disk_full"
+  | "permission"
+  | "cancelled"
+  | "not_found"
+  | "corrupt_data"
+  | "checksum_mismatch"
+  | "invalid_input"
+  | "already_running"
+  | "unsupported"
+  | "io"
+  | "internal";
+
+export interface LauncherError {
+  code: LauncherErrorCode;
+  message: string;
+  retryable: boolean;
+  context: Record<string, unknown>;
+}
+
+export function isLauncherError(error: unknown): error is LauncherError {
+  return (
+    typeof error === "object" &&
+    error !== null &&
+    typeof (error as LauncherError).code === "string" &&
+    typeof (error as LauncherError).message === "string"
+  );
+}
+
+export function errorMessage(error: unknown): string {
+  if (isLauncherError(error)) return error.message;
+  if (error instanceof Error) return error.message;
+  return String(error);
+}
+
+export function isRetryable(error: unknown): boolean {
+  return isLauncherError(error) && error.retryable;
+}
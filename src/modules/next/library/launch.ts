@@ -7,6 +7,8 @@ import { createExchangeCode } from "@/api/authentication/requests/verify";
 import { generateFilesResponse } from "@/api/main/requests/files";
 import { listen } from "@tauri-apps/api/event";
 import { generateAsteriaToken } from "@/api/authentication/requests/asteria";
+import { errorMessage } from "@/lib/errors";
+import type { TauriDownloadProgress } from "@/lib/download/downloadBuild";
 
 const appWindow = getCurrentWebviewWindow();
 
@@ -77,15 +79,26 @@ export async function getFilesToProcess(version: string) {
   return (await generateFilesResponse(useAuth.getState().token)).data;
 }
 
+/** Folder a launcher game file belongs in under the build at `path`. */
+function gameFilePath(path: string, name: string) {
+  return [".pak", ".sig", ".utoc", ".ucas"].some((ext) => name.includes(ext))
+    ? `${path}\\FortniteGame\\Content\\Paks\\`
+    : `${path}\\`;
+}
+
 export async function processFilesWithProgress(
   path: string,
   version: string,
-  files: { Name: string; Size: number; Url: string }[],
+  files: { Name: string; Size: number; Url: string; Hash?: string }[],
   setDownloadProgress: Function,
   setIsDownloadModalOpen: Function
 ) {
   const completedFiles: string[] = [];
   const fileNames = files.map((f) => f.Name);
+  // Game files are tracked by the download manager as `file:<destination>`.
+  const fileIds: Record<string, string> = Object.fromEntries(
+    files.map((f) => [`file:${gameFilePath(path, f.Name)}${f.Name}`, f.Name])
+  );
   const downloadSpeeds: Record<string, number> = {};
   const statusMessages: Record<string, string> = {};
   const downloadProgress: Record<string, number> = {};
@@ -100,20 +113,17 @@ export async function processFilesWithProgress(
 
   setIsDownloadModalOpen(true);
 
-  const unlistenProgress = await listen("download-progress", (event) => {
-    const { filename, progress, speed, message } = event.payload as {
-      filename: string;
-      progress: number;
-      speed: number;
-      message?: string;
-    };
+  const unlistenProgress = await listen<TauriDownloadProgress>("download:progress", (event) => {
+    const filename = fileIds[event.payload.build_id];
+    if (!filename) return;
+    const { paused } = event.payload;
+
+    const progress = Math.round(event.payload.percentage);
+    const speed = event.payload.speed / (1024 * 1024);
 
     downloadSpeeds[filename] = speed;
     downloadProgress[filename] = progress;
-
-    if (message) {
-      statusMessages[filename] = message;
-    }
+    statusMessages[filename] = paused ? "Paused" : "Downloading...";
 
     console.log(`Downloading ${filename}: ${progress}% (${speed.toFixed(2)} MB/s)`);
 
@@ -125,8 +135,9 @@ export async function processFilesWithProgress(
     }));
   });
 
-  const unlistenCompleted = await listen("download-completed", (event) => {
-    const { filename } = event.payload as { filename: string };
+  const unlistenCompleted = await listen<string>("download:completed", (event) => {
+    const filename = fileIds[event.payload];
+    if (!filename) return;
     console.log(`Download completed for ${filename}`);
 
     if (!completedFiles.includes(filename)) {
@@ -154,14 +165,13 @@ export async function processFilesWithProgress(
     if (!file) continue;
 
     console.log(`Processing: ${file.Name}`);
-    const downloadPath = [".pak", ".sig", ".utoc", ".ucas"].some((ext) => file.Name.includes(ext))
-      ? `${path}\\FortniteGame\\Content\\Paks\\`
-      : `${path}\\`;
+    const downloadPath = gameFilePath(path, file.Name);
 
     try {
       const exists = await invoke("check_file_exists_and_size", {
         path: `${downloadPath}${file.Name}`,
         size: file.Size,
+        hash: file.Hash ?? null,
       });
 
       if (!exists || [".cer", ".bin"].some((ext) => file.Name.includes(ext))) {
@@ -174,6 +184,7 @@ export async function processFilesWithProgress(
         await invoke("download_game_file", {
           url: file.Url,
           dest: `${downloadPath}${file.Name}`,
+          hash: file.Hash ?? null,
         });
       } else {
         if (!completedFiles.includes(file.Name)) {
@@ -188,9 +199,9 @@ export async function processFilesWithProgress(
       }
     } catch (error) {
       console.error(`Error processing file ${file.Name}:`, error);
-      statusMessages[file.Name] = `Error: ${error}`;
+      statusMessages[file.Name] = `Error: ${errorMessage(error)}`;
 
-      failed.push({ file, error: String(error) });
+      failed.push({ file, error: errorMessage(error) });
 
       if (!completedFiles.includes(file.Name)) {
         completedFiles.push(file.Name);